@@ -0,0 +1,7 @@
+//!
+//! Data-driven definitions loaded at startup
+//! 
+
+pub mod tower_catalog;
+pub mod enemy_catalog;
+pub mod wave_catalog;
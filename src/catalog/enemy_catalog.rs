@@ -0,0 +1,358 @@
+//!
+//! Enemy Definitions
+//! 
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{DamageType, EnemyType, Layer};
+
+/// The enemy catalog shipped with the game
+pub const DEFAULT_ENEMY_CATALOG: &str = include_str!("../../assets/enemies.ron");
+
+/// The stats of a single type of enemy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnemyDefinition {
+    // The sprite drawn for the enemy
+    pub sprite: String,
+    // The color the enemy is drawn in
+    #[serde(serialize_with = "serialize_color")]
+    pub color: Color,
+    // The number of positions the enemy moves each second
+    pub speed: u32,
+    // The health the enemy spawns with
+    pub health: u32,
+    // The damage dealt to the base when the enemy reaches it
+    pub damage: u32,
+    // The points gained from killing the enemy
+    pub bounty: u32,
+    // The physical damage blocked each time the enemy takes damage
+    #[serde(default)]
+    pub armor: u32,
+    // The percentage of magic damage the enemy ignores
+    #[serde(default)]
+    pub resistance: u32,
+    // The percentage chance the enemy dodges each attack
+    #[serde(default)]
+    pub dodge_chance: u32,
+    // Whether the enemy walks along the path or flies above it
+    #[serde(default)]
+    pub layer: Layer,
+    // Whether the enemy can only be targeted within the range of a tower with detection
+    #[serde(default)]
+    pub stealth: bool,
+    // The phases a boss goes through as it loses health (in order, empty for regular enemies)
+    #[serde(default)]
+    pub phases: Vec<BossPhase>,
+    // The abilities the enemy uses when it is killed
+    #[serde(default)]
+    pub on_death: Vec<DeathAbility>,
+    // The ability the enemy uses on nearby enemies every so often (if it supports other enemies)
+    #[serde(default)]
+    pub support: Option<SupportAbility>,
+}
+
+/// Something a support enemy does for the enemies near it on the path every interval (in milliseconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SupportAbility {
+    // Restore an amount of health to every enemy within a radius (in path positions)
+    Heal { amount: u32, radius: u32, interval: u32 },
+    // Grant a shield absorbing an amount of damage for a duration (in milliseconds) to every
+    // enemy within a radius (in path positions)
+    Shield { amount: u32, duration: u32, radius: u32, interval: u32 },
+}
+
+impl SupportAbility {
+    /// The number of path positions away the ability reaches
+    pub fn radius(&self) -> u32 {
+        match self {
+            Self::Heal { radius, .. } | Self::Shield { radius, .. } => *radius,
+        }
+    }
+
+    /// The time (in milliseconds) between uses of the ability
+    pub fn interval(&self) -> u32 {
+        match self {
+            Self::Heal { interval, .. } | Self::Shield { interval, .. } => *interval,
+        }
+    }
+}
+
+/// Something an enemy does when it is killed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathAbility {
+    // Split into a number of enemies of another type where the enemy died
+    Split { enemy: EnemyType, count: u32 },
+}
+
+/// A change in a boss's behavior once its health drops to a threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BossPhase {
+    // The percentage of its catalog health the boss enters the phase at
+    pub health_percent: u32,
+    // The boss's new speed (in positions per second)
+    #[serde(default)]
+    pub speed: Option<u32>,
+    // The enemies spawned behind the boss as it enters the phase
+    #[serde(default)]
+    pub minions: Vec<EnemyType>,
+    // The shield (damage absorbed before the boss loses health) the boss gains
+    #[serde(default)]
+    pub shield: u32,
+}
+
+/// Write a color the same way it is read from a catalog file ("Red", "#FF8800", ...)
+fn serialize_color<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(color)
+}
+
+/// Every type of enemy in the game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnemyCatalog {
+    pub enemies: BTreeMap<EnemyType, EnemyDefinition>,
+}
+
+impl EnemyCatalog {
+    /// Parse an enemy catalog from the contents of a catalog file
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let catalog: Self = ron::from_str(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Load an enemy catalog from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Make sure every enemy in the catalog moves and only spawns enemies in the catalog
+    pub fn validate(&self) -> io::Result<()> {
+        for (enemy_type, definition) in self.enemies.iter() {
+            if definition.speed == 0 || definition.phases.iter().any(|v| v.speed == Some(0)) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Enemy \"{}\" must have a speed above 0", enemy_type.0),
+                ));
+            }
+            if definition.resistance > 100 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Enemy \"{}\" can't resist more than 100% of magic damage", enemy_type.0),
+                ));
+            }
+
+            let split_enemies = definition.on_death.iter().map(|v| match v {
+                DeathAbility::Split { enemy, .. } => enemy,
+            });
+            let minions = definition.phases.iter().flat_map(|v| v.minions.iter());
+            for spawned_enemy in split_enemies.chain(minions) {
+                if !self.contains(spawned_enemy) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Enemy \"{}\" spawns unknown enemy \"{}\"", enemy_type.0, spawned_enemy.0),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether an enemy type is in the catalog
+    pub fn contains(&self, enemy_type: &EnemyType) -> bool {
+        self.enemies.contains_key(enemy_type)
+    }
+
+    /// Get the definition of an enemy type
+    pub fn get(&self, enemy_type: &EnemyType) -> &EnemyDefinition {
+        self.enemies.get(enemy_type).expect("Enemy Type must be in the Enemy Catalog")
+    }
+}
+
+impl EnemyDefinition {
+    /// Whether the enemy is a boss
+    pub fn is_boss(&self) -> bool {
+        !self.phases.is_empty()
+    }
+
+    /// The damage the enemy takes after its armor or resistance (depending on the damage type)
+    pub fn mitigated_damage(&self, damage_type: DamageType, damage: u32) -> u32 {
+        match damage_type {
+            DamageType::Physical => damage.saturating_sub(self.armor),
+            DamageType::Magic => damage * (100 - self.resistance.min(100)) / 100,
+            DamageType::Pierce => damage,
+        }
+    }
+}
+
+impl Default for EnemyCatalog {
+    fn default() -> Self {
+        Self::parse(DEFAULT_ENEMY_CATALOG).expect("Default Enemy Catalog must be valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_enemy_catalog() {
+        let catalog = EnemyCatalog::default();
+
+        let base = catalog.get(&EnemyType::from("base"));
+        assert_eq!(base.sprite, "X");
+        assert_eq!(base.color, Color::Red);
+        assert_eq!((base.speed, base.health, base.damage, base.bounty), (1, 1, 1, 1));
+
+        let final_enemy = catalog.get(&EnemyType::from("final"));
+        assert_eq!(final_enemy.color, Color::Magenta);
+        assert_eq!((final_enemy.speed, final_enemy.health, final_enemy.damage, final_enemy.bounty), (1, 20, 20, 10));
+        assert_eq!((final_enemy.armor, final_enemy.resistance), (2, 25));
+    }
+
+    #[test]
+    fn test_mitigated_damage() {
+        let catalog = EnemyCatalog::parse(r#"(
+            enemies: {
+                "tank": (sprite: "T", color: "gray", speed: 1, health: 50, damage: 5, bounty: 5, armor: 2, resistance: 50),
+            },
+        )"#).unwrap();
+
+        let tank = catalog.get(&EnemyType::from("tank"));
+        assert_eq!(tank.mitigated_damage(DamageType::Physical, 10), 8);
+        assert_eq!(tank.mitigated_damage(DamageType::Physical, 1), 0);
+        assert_eq!(tank.mitigated_damage(DamageType::Magic, 10), 5);
+        assert_eq!(tank.mitigated_damage(DamageType::Pierce, 10), 10);
+    }
+
+    #[test]
+    fn test_parse_enemy_catalog_with_new_enemy() {
+        let catalog = EnemyCatalog::parse(r##"(
+            enemies: {
+                "runner": (sprite: "R", color: "#ff8800", speed: 5, health: 1, damage: 1, bounty: 3),
+            },
+        )"##).unwrap();
+
+        let runner = catalog.get(&EnemyType::from("runner"));
+        assert_eq!(runner.color, Color::Rgb(255, 136, 0));
+        assert_eq!(runner.speed, 5);
+    }
+
+    #[test]
+    fn test_default_enemy_catalog_boss() {
+        let catalog = EnemyCatalog::default();
+
+        let boss = catalog.get(&EnemyType::from("boss"));
+        assert!(boss.is_boss());
+        assert!(!catalog.get(&EnemyType::from("final")).is_boss());
+        assert_eq!(boss.phases.iter().map(|v| v.health_percent).collect::<Vec<u32>>(), vec![75, 50, 25]);
+        assert_eq!(boss.phases[0].minions.len(), 3);
+        assert_eq!(boss.phases[1].speed, Some(2));
+        assert_eq!(boss.phases[2].shield, 30);
+    }
+
+    #[test]
+    fn test_default_enemy_catalog_splitter() {
+        let catalog = EnemyCatalog::default();
+
+        let splitter = catalog.get(&EnemyType::from("splitter"));
+        assert_eq!(splitter.on_death, vec![DeathAbility::Split { enemy: EnemyType::from("base"), count: 3 }]);
+        assert!(catalog.get(&EnemyType::from("base")).on_death.is_empty());
+    }
+
+    #[test]
+    fn test_default_enemy_catalog_support() {
+        let catalog = EnemyCatalog::default();
+
+        assert_eq!(
+            catalog.get(&EnemyType::from("healer")).support,
+            Some(SupportAbility::Heal { amount: 2, radius: 3, interval: 2000 }),
+        );
+        assert_eq!(catalog.get(&EnemyType::from("shield-bearer")).support.map(|v| v.radius()), Some(3));
+        assert_eq!(catalog.get(&EnemyType::from("base")).support, None);
+    }
+
+    #[test]
+    fn test_default_enemy_catalog_stealth() {
+        let catalog = EnemyCatalog::default();
+
+        assert!(catalog.get(&EnemyType::from("ghost")).stealth);
+        assert_eq!(catalog.get(&EnemyType::from("ghost")).dodge_chance, 15);
+        assert!(!catalog.get(&EnemyType::from("base")).stealth);
+    }
+
+    #[test]
+    fn test_default_enemy_catalog_flying() {
+        let catalog = EnemyCatalog::default();
+
+        assert_eq!(catalog.get(&EnemyType::from("flyer")).layer, Layer::Air);
+        assert_eq!(catalog.get(&EnemyType::from("base")).layer, Layer::Ground);
+    }
+
+    #[test]
+    fn test_parse_enemy_catalog_with_unknown_split_enemy() {
+        let catalog = EnemyCatalog::parse(r#"(
+            enemies: {
+                "splitter": (
+                    sprite: "Y", color: "yellow", speed: 1, health: 6, damage: 3, bounty: 3,
+                    on_death: [Split(enemy: "missing", count: 3)],
+                ),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_enemy_catalog_with_unknown_minion() {
+        let catalog = EnemyCatalog::parse(r#"(
+            enemies: {
+                "boss": (
+                    sprite: "W", color: "magenta", speed: 1, health: 100, damage: 50, bounty: 100,
+                    phases: [(health_percent: 50, minions: ["missing"])],
+                ),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_enemy_catalog_with_zero_speed() {
+        let catalog = EnemyCatalog::parse(r#"(
+            enemies: {
+                "base": (sprite: "X", color: "red", speed: 0, health: 1, damage: 1, bounty: 1),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_enemy_catalog_with_too_much_resistance() {
+        let catalog = EnemyCatalog::parse(r#"(
+            enemies: {
+                "base": (sprite: "X", color: "red", speed: 1, health: 1, damage: 1, bounty: 1, resistance: 150),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_enemy_catalog_with_invalid_color() {
+        let catalog = EnemyCatalog::parse(r#"(
+            enemies: {
+                "base": (sprite: "X", color: "not a color", speed: 1, health: 1, damage: 1, bounty: 1),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,437 @@
+//!
+//! Tower Definitions
+//! 
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DamageType, Layer, StatusEffect, TowerType};
+
+/// The tower catalog shipped with the game
+pub const DEFAULT_TOWER_CATALOG: &str = include_str!("../../assets/towers.ron");
+
+/// The stats of a single type of tower
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TowerDefinition {
+    // The sprite drawn for the tower
+    pub sprite: String,
+    // The damage dealt with each attack
+    pub damage: u32,
+    // The kind of damage dealt
+    #[serde(default)]
+    pub damage_type: DamageType,
+    // The time (in milliseconds) between attacks
+    pub fire_interval: u32,
+    // The radius (in map tiles) around the tower it can attack
+    pub range: u32,
+    // The radius (in map tiles) around the targeted enemy that every enemy is hit in (0 only hits the target)
+    #[serde(default)]
+    pub splash_radius: u32,
+    // The status effect applied to every enemy hit
+    #[serde(default)]
+    pub status_effect: Option<StatusEffect>,
+    // The percentage chance an attack is a critical hit
+    #[serde(default)]
+    pub crit_chance: u32,
+    // The percentage of its damage a critical hit deals
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: u32,
+    // The speed (in map tiles per second) the tower's shots fly at (0 hits the target instantly)
+    #[serde(default)]
+    pub projectile_speed: u32,
+    // The layers of enemies the tower can hit
+    #[serde(default = "ground_layer")]
+    pub layers: Vec<Layer>,
+    // Whether the tower reveals stealth enemies within its range to every tower
+    #[serde(default)]
+    pub detection: bool,
+    // The points the tower earns at the end of every wave
+    #[serde(default)]
+    pub income: u32,
+    // The points needed to upgrade into this tower
+    pub cost: u32,
+    // The tower this tower can be upgraded into
+    #[serde(default)]
+    pub upgrade: Option<TowerType>,
+    // Towers starting separate upgrade lines this tower can be upgraded into instead
+    #[serde(default)]
+    pub branches: Vec<TowerType>,
+}
+
+/// Critical hits deal double damage unless configured otherwise
+fn default_crit_multiplier() -> u32 {
+    200
+}
+
+/// Towers only hit ground enemies unless configured otherwise
+fn ground_layer() -> Vec<Layer> {
+    vec![Layer::Ground]
+}
+
+/// Every type of tower in the game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TowerCatalog {
+    pub towers: BTreeMap<TowerType, TowerDefinition>,
+}
+
+impl TowerCatalog {
+    /// Parse a tower catalog from the contents of a catalog file
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let catalog: Self = ron::from_str(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Load a tower catalog from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Make sure every tower in the catalog can be built, upgraded, and sold back into the
+    /// tower it was upgraded from
+    pub fn validate(&self) -> io::Result<()> {
+        if !self.towers.contains_key(&TowerType::broken()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The tower catalog must define a \"broken\" tower",
+            ));
+        }
+
+        for (tower_type, definition) in self.towers.iter() {
+            if definition.fire_interval == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Tower \"{}\" must have a fire_interval above 0", tower_type.0),
+                ));
+            }
+            for upgrade in definition.upgrade.iter().chain(definition.branches.iter()) {
+                if !self.towers.contains_key(upgrade) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Tower \"{}\" upgrades into unknown tower \"{}\"", tower_type.0, upgrade.0),
+                    ));
+                }
+                // Broken towers are the empty tiles towers are built on (they can't be upgraded into)
+                if *upgrade == TowerType::broken() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Tower \"{}\" upgrades into the \"broken\" tower", tower_type.0),
+                    ));
+                }
+            }
+        }
+
+        // Every tower must be upgraded into from at most one tower (the tower it is sold back into)
+        let mut parents: BTreeMap<&TowerType, &TowerType> = BTreeMap::new();
+        for (tower_type, definition) in self.towers.iter() {
+            for upgrade in definition.upgrade.iter().chain(definition.branches.iter()) {
+                if let Some(parent) = parents.insert(upgrade, tower_type) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Tower \"{}\" is upgraded into from both \"{}\" and \"{}\"",
+                            upgrade.0, parent.0, tower_type.0,
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Upgrade lines must not loop back into themselves
+        for tower_type in self.towers.keys() {
+            let mut downgrade = tower_type;
+            let mut downgrades = 0;
+            while let Some(parent) = parents.get(downgrade) {
+                downgrades += 1;
+                if downgrades > self.towers.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Tower \"{}\" is part of an upgrade cycle", tower_type.0),
+                    ));
+                }
+                downgrade = parent;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the definition of a tower type
+    pub fn get(&self, tower_type: &TowerType) -> &TowerDefinition {
+        self.towers.get(tower_type).expect("Tower Type must be in the Tower Catalog")
+    }
+
+    /// The tower a tower type upgrades into
+    pub fn upgrade(&self, tower_type: &TowerType) -> Option<&TowerType> {
+        self.get(tower_type).upgrade.as_ref()
+    }
+
+    /// The towers a tower type branches into
+    pub fn branches(&self, tower_type: &TowerType) -> &[TowerType] {
+        &self.get(tower_type).branches
+    }
+
+    /// One of the towers a tower type branches into
+    pub fn branch(&self, tower_type: &TowerType, branch: usize) -> Option<&TowerType> {
+        self.get(tower_type).branches.get(branch)
+    }
+
+    /// The tower a tower type becomes when it is sold
+    pub fn downgrade(&self, tower_type: &TowerType) -> Option<&TowerType> {
+        self.towers.iter()
+            .find(|(_, definition)| {
+                definition.upgrade.as_ref() == Some(tower_type) || definition.branches.contains(tower_type)
+            })
+            .map(|(downgrade, _)| downgrade)
+    }
+
+    /// The cost to upgrade a tower (0 if the tower can't be upgraded)
+    pub fn upgrade_price(&self, tower_type: &TowerType) -> u32 {
+        match self.upgrade(tower_type) {
+            Some(upgrade) => self.get(upgrade).cost,
+            None => 0,
+        }
+    }
+
+    /// The cost to upgrade a tower into one of its branches (0 if the tower doesn't have the branch)
+    pub fn branch_price(&self, tower_type: &TowerType, branch: usize) -> u32 {
+        match self.branch(tower_type, branch) {
+            Some(branch) => self.get(branch).cost,
+            None => 0,
+        }
+    }
+
+    /// The total cost of a tower (the costs of every tower upgraded through to reach it)
+    pub fn total_cost(&self, tower_type: &TowerType) -> u32 {
+        let mut total_cost = self.get(tower_type).cost;
+        let mut tower_type = tower_type;
+        while let Some(downgrade) = self.downgrade(tower_type) {
+            total_cost += self.get(downgrade).cost;
+            tower_type = downgrade;
+        }
+        total_cost
+    }
+}
+
+impl Default for TowerCatalog {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TOWER_CATALOG).expect("Default Tower Catalog must be valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_tower_catalog_upgrade_chain() {
+        let catalog = TowerCatalog::default();
+
+        let mut tower_type = TowerType::broken();
+        let mut upgrade_prices = Vec::new();
+        while let Some(upgrade) = catalog.upgrade(&tower_type) {
+            upgrade_prices.push(catalog.upgrade_price(&tower_type));
+            tower_type = upgrade.clone();
+        }
+
+        assert_eq!(tower_type, TowerType::from("final"));
+        assert_eq!(upgrade_prices, vec![10, 20, 30, 40, 50, 60]);
+        assert_eq!(catalog.upgrade_price(&tower_type), 0);
+    }
+
+    #[test]
+    fn test_tower_catalog_downgrade() {
+        let catalog = TowerCatalog::default();
+
+        assert_eq!(catalog.downgrade(&TowerType::from("second")), Some(&TowerType::from("base")));
+        assert_eq!(catalog.downgrade(&TowerType::from("base")), Some(&TowerType::broken()));
+        assert_eq!(catalog.downgrade(&TowerType::broken()), None);
+    }
+
+    #[test]
+    fn test_default_tower_catalog_splash_line() {
+        let catalog = TowerCatalog::default();
+
+        let splash = catalog.branch(&TowerType::broken(), 0).unwrap();
+        assert_eq!(splash, &TowerType::from("splash"));
+        assert_eq!(catalog.branch_price(&TowerType::broken(), 0), 15);
+        assert!(catalog.get(splash).splash_radius > 0);
+        assert_eq!(catalog.upgrade(splash), Some(&TowerType::from("splash-2")));
+        assert_eq!(catalog.downgrade(splash), Some(&TowerType::broken()));
+        assert_eq!(catalog.branch_price(splash, 0), 0);
+    }
+
+    #[test]
+    fn test_default_tower_catalog_frost_line() {
+        let catalog = TowerCatalog::default();
+
+        let frost = catalog.branch(&TowerType::from("base"), 0).unwrap();
+        assert_eq!(frost, &TowerType::from("frost"));
+        assert_eq!(catalog.get(frost).status_effect, Some(StatusEffect::Slow { percent: 50, duration: 2000 }));
+
+        let frost_2 = catalog.upgrade(frost).unwrap();
+        assert_eq!(catalog.get(frost_2).status_effect, Some(StatusEffect::Freeze { duration: 1000 }));
+        assert_eq!(catalog.downgrade(frost), Some(&TowerType::from("base")));
+    }
+
+    #[test]
+    fn test_default_tower_catalog_poison_line() {
+        let catalog = TowerCatalog::default();
+
+        let poison = catalog.branch(&TowerType::from("second"), 0).unwrap();
+        assert_eq!(poison, &TowerType::from("poison"));
+        assert!(catalog.get(poison).status_effect.is_some_and(|v| v.stacks()));
+        assert_eq!(catalog.upgrade(poison), Some(&TowerType::from("poison-2")));
+    }
+
+    #[test]
+    fn test_default_tower_catalog_banks() {
+        let catalog = TowerCatalog::default();
+
+        let bank = catalog.branch(&TowerType::broken(), 1).unwrap();
+        assert_eq!(bank, &TowerType::from("bank"));
+        assert_eq!(catalog.get(bank).income, 8);
+        assert_eq!(catalog.get(bank).damage, 0);
+        assert_eq!(catalog.get(catalog.upgrade(bank).unwrap()).income, 20);
+        assert_eq!(catalog.get(&TowerType::from("base")).income, 0);
+    }
+
+    #[test]
+    fn test_default_tower_catalog_second_tier_branches() {
+        let catalog = TowerCatalog::default();
+
+        let second = TowerType::from("second");
+        assert_eq!(
+            catalog.branches(&second),
+            &[TowerType::from("poison"), TowerType::from("rapid"), TowerType::from("sniper")],
+        );
+        assert_eq!(catalog.branch_price(&second, 2), 35);
+        assert_eq!(catalog.branch_price(&second, 3), 0);
+        assert_eq!(catalog.downgrade(&TowerType::from("sniper")), Some(&second));
+    }
+
+    #[test]
+    fn test_default_tower_catalog_detector() {
+        let catalog = TowerCatalog::default();
+
+        let watchtower = catalog.branch(&TowerType::from("fourth"), 0).unwrap();
+        assert_eq!(watchtower, &TowerType::from("watchtower"));
+        assert!(catalog.get(watchtower).detection);
+        assert!(!catalog.get(&TowerType::from("fourth")).detection);
+    }
+
+    #[test]
+    fn test_default_tower_catalog_layers() {
+        let catalog = TowerCatalog::default();
+
+        let base = catalog.get(&TowerType::from("base"));
+        assert_eq!(base.layers, vec![Layer::Ground]);
+        let second = catalog.get(&TowerType::from("second"));
+        assert_eq!(second.layers, vec![Layer::Ground, Layer::Air]);
+    }
+
+    #[test]
+    fn test_default_tower_catalog_cannon() {
+        let catalog = TowerCatalog::default();
+
+        let cannon = catalog.branch(&TowerType::from("fifth"), 0).unwrap();
+        assert_eq!(cannon, &TowerType::from("cannon"));
+        assert!(catalog.get(cannon).projectile_speed > 0);
+        assert_eq!(catalog.get(&TowerType::from("base")).projectile_speed, 0);
+    }
+
+    #[test]
+    fn test_default_tower_catalog_crits() {
+        let catalog = TowerCatalog::default();
+
+        let cannon = catalog.get(&TowerType::from("cannon"));
+        assert_eq!((cannon.crit_chance, cannon.crit_multiplier), (25, 200));
+        assert_eq!(catalog.get(&TowerType::from("base")).crit_chance, 0);
+    }
+
+    #[test]
+    fn test_tower_catalog_total_cost() {
+        let catalog = TowerCatalog::default();
+
+        assert_eq!(catalog.total_cost(&TowerType::broken()), 0);
+        assert_eq!(catalog.total_cost(&TowerType::from("final")), 210);
+        assert_eq!(catalog.total_cost(&TowerType::from("sniper")), 65);
+    }
+
+    #[test]
+    fn test_parse_tower_catalog_without_broken_tower() {
+        let catalog = TowerCatalog::parse(r#"(
+            towers: {
+                "base": (sprite: "!", damage: 1, fire_interval: 1000, range: 2, cost: 10),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_tower_catalog_with_unknown_branch() {
+        let catalog = TowerCatalog::parse(r#"(
+            towers: {
+                "broken": (sprite: "-", damage: 0, fire_interval: 1000, range: 0, cost: 0, branches: ["splash"]),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_tower_catalog_with_upgrade_cycle() {
+        let catalog = TowerCatalog::parse(r#"(
+            towers: {
+                "broken": (sprite: "-", damage: 0, fire_interval: 1000, range: 0, cost: 0),
+                "a": (sprite: "a", damage: 1, fire_interval: 1000, range: 2, cost: 10, upgrade: Some("b")),
+                "b": (sprite: "b", damage: 1, fire_interval: 1000, range: 2, cost: 10, upgrade: Some("a")),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_tower_catalog_with_two_parents() {
+        let catalog = TowerCatalog::parse(r#"(
+            towers: {
+                "broken": (sprite: "-", damage: 0, fire_interval: 1000, range: 0, cost: 0, upgrade: Some("base")),
+                "base": (sprite: "!", damage: 1, fire_interval: 1000, range: 2, cost: 10, branches: ["frost"]),
+                "splash": (sprite: "*", damage: 1, fire_interval: 1000, range: 2, cost: 10, upgrade: Some("frost")),
+                "frost": (sprite: "~", damage: 1, fire_interval: 1000, range: 2, cost: 10),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_tower_catalog_upgrading_into_broken() {
+        let catalog = TowerCatalog::parse(r#"(
+            towers: {
+                "broken": (sprite: "-", damage: 0, fire_interval: 1000, range: 0, cost: 0),
+                "base": (sprite: "!", damage: 1, fire_interval: 1000, range: 2, cost: 10, branches: ["broken"]),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_tower_catalog_with_unknown_upgrade() {
+        let catalog = TowerCatalog::parse(r#"(
+            towers: {
+                "broken": (sprite: "-", damage: 0, fire_interval: 1000, range: 0, cost: 0, upgrade: Some("base")),
+            },
+        )"#);
+
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,336 @@
+//!
+//! Wave Definitions
+//! 
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::EnemyType;
+use crate::catalog::enemy_catalog::EnemyCatalog;
+
+/// The wave catalog shipped with the game
+pub const DEFAULT_WAVE_CATALOG: &str = include_str!("../../assets/waves.ron");
+
+/// A group of enemies of the same type spawned in a wave
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaveGroup {
+    // The type of enemy in the group
+    pub enemy: EnemyType,
+    // The number of enemies in the group
+    pub count: u32,
+    // The number of positions between each enemy in the group
+    pub spacing: u32,
+    // The number of positions behind the start of the track the group starts at
+    pub delay: u32,
+}
+
+impl WaveGroup {
+    /// The positions the enemies in the group spawn at for a track of a given length
+    pub fn positions(&self, track_length: u32) -> Vec<u32> {
+        (0..self.count).map(|v| track_length + self.delay + v * self.spacing).collect()
+    }
+}
+
+/// The enemies spawned at the start of a level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wave {
+    pub groups: Vec<WaveGroup>,
+}
+
+/// How waves are generated once the scripted waves have run out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndlessWaves {
+    // The enemy types in every endless wave
+    pub enemies: Vec<EnemyType>,
+    // The number of enemies of each type in the first endless wave
+    pub count: u32,
+    // The additional enemies of each type for every following endless wave
+    pub count_increase: u32,
+    // The number of positions between each enemy
+    pub spacing: u32,
+}
+
+impl EndlessWaves {
+    /// Make sure every endless wave spawns enemies (so endless levels can't clear as soon as they start)
+    pub fn validate(&self) -> io::Result<()> {
+        if self.enemies.is_empty() || self.count == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Endless waves must have at least one enemy type and a count above 0",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Generate the nth endless wave (starting at 0)
+    pub fn wave(&self, endless_level: u32) -> Wave {
+        let count = self.count + endless_level * self.count_increase;
+        let mut delay = 0;
+        let groups = self.enemies.iter().map(|enemy| {
+            let group = WaveGroup {
+                enemy: enemy.clone(),
+                count,
+                spacing: self.spacing,
+                delay,
+            };
+            delay += count * self.spacing;
+            group
+        }).collect();
+        Wave { groups }
+    }
+}
+
+/// A boss added to the end of every few waves
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BossWaves {
+    // The type of boss enemy
+    pub enemy: EnemyType,
+    // The number of levels between each boss (a boss appears on every level divisible by it)
+    pub every: u32,
+}
+
+/// The points paid out at the end of every wave
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaveRewards {
+    // The percentage of banked points paid as interest
+    #[serde(default)]
+    pub interest_percent: u32,
+    // The most points paid as interest
+    #[serde(default)]
+    pub max_interest: u32,
+    // The points paid for clearing a wave without the base taking damage
+    #[serde(default)]
+    pub clear_bonus: u32,
+}
+
+impl WaveRewards {
+    /// The interest paid on some banked points
+    pub fn interest(&self, points: u32) -> u32 {
+        (points as u64 * self.interest_percent as u64 / 100).min(self.max_interest as u64) as u32
+    }
+}
+
+/// The waves of enemies for every level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaveCatalog {
+    pub waves: Vec<Wave>,
+    pub endless: EndlessWaves,
+    #[serde(default)]
+    pub boss: Option<BossWaves>,
+    #[serde(default)]
+    pub rewards: WaveRewards,
+}
+
+impl WaveCatalog {
+    /// Parse a wave catalog from the contents of a catalog file
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let catalog: Self = ron::from_str(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        catalog.endless.validate()?;
+        Ok(catalog)
+    }
+
+    /// Load a wave catalog from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Make sure the endless waves spawn enemies and every enemy the waves spawn is in an enemy catalog
+    pub fn validate(&self, enemy_catalog: &EnemyCatalog) -> io::Result<()> {
+        self.endless.validate()?;
+
+        let scripted_enemies = self.waves.iter().flat_map(|v| v.groups.iter().map(|v| &v.enemy));
+        let endless_enemies = self.endless.enemies.iter();
+        let boss = self.boss.iter().map(|v| &v.enemy);
+        for enemy in scripted_enemies.chain(endless_enemies).chain(boss) {
+            if !enemy_catalog.contains(enemy) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("The waves spawn enemy \"{}\" which isn't in the enemy catalog", enemy.0),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The wave for a given level (starting at level 1), generating endless waves once
+    /// the scripted waves have run out and adding a boss behind the wave on boss levels
+    pub fn wave(&self, level: u32) -> Wave {
+        let index = level.saturating_sub(1);
+        let mut wave = match self.waves.get(index as usize) {
+            Some(wave) => wave.clone(),
+            None => self.endless.wave(index - self.waves.len() as u32),
+        };
+
+        if let Some(boss) = self.boss.as_ref().filter(|v| v.every > 0 && level.is_multiple_of(v.every)) {
+            let delay = wave.groups.iter().map(|v| v.delay + v.count * v.spacing).max().unwrap_or(0);
+            wave.groups.push(WaveGroup {
+                enemy: boss.enemy.clone(),
+                count: 1,
+                spacing: 1,
+                delay,
+            });
+        }
+        wave
+    }
+}
+
+impl Default for WaveCatalog {
+    fn default() -> Self {
+        Self::parse(DEFAULT_WAVE_CATALOG).expect("Default Wave Catalog must be valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_WAVE_CATALOG: &str = r#"(
+        waves: [
+            (groups: [(enemy: "base", count: 3, spacing: 2, delay: 0)]),
+            (groups: [
+                (enemy: "base", count: 2, spacing: 1, delay: 0),
+                (enemy: "second", count: 1, spacing: 1, delay: 5),
+            ]),
+        ],
+        endless: (
+            enemies: ["second", "third"],
+            count: 4,
+            count_increase: 3,
+            spacing: 2,
+        ),
+    )"#;
+
+    #[test]
+    fn test_wave_group_positions() {
+        let group = WaveGroup {
+            enemy: EnemyType::from("base"),
+            count: 3,
+            spacing: 2,
+            delay: 5,
+        };
+
+        assert_eq!(group.positions(100), vec![105, 107, 109]);
+    }
+
+    #[test]
+    fn test_scripted_waves() {
+        let catalog = WaveCatalog::parse(TEST_WAVE_CATALOG).unwrap();
+
+        assert_eq!(catalog.wave(1).groups[0].positions(100), vec![100, 102, 104]);
+        assert_eq!(catalog.wave(2).groups.len(), 2);
+        assert_eq!(catalog.wave(2).groups[1].enemy, EnemyType::from("second"));
+        assert_eq!(catalog.wave(2).groups[1].positions(100), vec![105]);
+    }
+
+    #[test]
+    fn test_endless_waves_after_scripted_waves() {
+        let catalog = WaveCatalog::parse(TEST_WAVE_CATALOG).unwrap();
+
+        // The first endless wave
+        let wave = catalog.wave(3);
+        assert_eq!(wave.groups.len(), 2);
+        assert_eq!(wave.groups[0].enemy, EnemyType::from("second"));
+        assert_eq!(wave.groups[0].positions(100), vec![100, 102, 104, 106]);
+        assert_eq!(wave.groups[1].enemy, EnemyType::from("third"));
+        assert_eq!(wave.groups[1].positions(100), vec![108, 110, 112, 114]);
+
+        // Endless waves keep getting bigger
+        let wave = catalog.wave(5);
+        assert_eq!(wave.groups[0].count, 10);
+        assert_eq!(wave.groups[1].delay, 20);
+    }
+
+    #[test]
+    fn test_boss_waves() {
+        let mut catalog = WaveCatalog::parse(TEST_WAVE_CATALOG).unwrap();
+        catalog.boss = Some(BossWaves {
+            enemy: EnemyType::from("boss"),
+            every: 2,
+        });
+
+        // No boss on odd levels
+        assert_eq!(catalog.wave(1).groups.len(), 1);
+
+        // The boss follows the last group of the wave
+        let wave = catalog.wave(2);
+        assert_eq!(wave.groups.len(), 3);
+        assert_eq!(wave.groups[2].enemy, EnemyType::from("boss"));
+        assert_eq!(wave.groups[2].positions(100), vec![106]);
+    }
+
+    #[test]
+    fn test_wave_rewards_interest() {
+        let catalog = WaveCatalog::parse(TEST_WAVE_CATALOG).unwrap();
+        let default_catalog = WaveCatalog::default();
+
+        // No rewards unless the catalog has them
+        assert_eq!(catalog.rewards, WaveRewards::default());
+        assert_eq!(catalog.rewards.interest(100), 0);
+
+        // Interest is capped
+        assert_eq!(default_catalog.rewards.interest(100), 5);
+        assert_eq!(default_catalog.rewards.interest(1000), 20);
+        assert_eq!(default_catalog.rewards.interest(u32::MAX), 20);
+    }
+
+    #[test]
+    fn test_validate_waves_against_enemy_catalog() {
+        let catalog = WaveCatalog::parse(TEST_WAVE_CATALOG).unwrap();
+        let enemy_catalog = EnemyCatalog::parse(r#"(
+            enemies: {
+                "base": (sprite: "X", color: "red", speed: 1, health: 1, damage: 1, bounty: 1),
+            },
+        )"#).unwrap();
+
+        // The default enemies include every enemy in the waves but an enemy catalog without them doesn't
+        assert!(catalog.validate(&EnemyCatalog::default()).is_ok());
+        assert!(WaveCatalog::default().validate(&EnemyCatalog::default()).is_ok());
+        assert_eq!(catalog.validate(&enemy_catalog).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_validate_endless_and_boss_enemies() {
+        let mut catalog = WaveCatalog::default();
+        catalog.boss.as_mut().unwrap().enemy = EnemyType::from("bos");
+        assert_eq!(catalog.validate(&EnemyCatalog::default()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut catalog = WaveCatalog::default();
+        catalog.endless.enemies.push(EnemyType::from("fianl"));
+        assert_eq!(catalog.validate(&EnemyCatalog::default()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_validate_endless_waves_spawn_enemies() {
+        let mut catalog = WaveCatalog::default();
+        catalog.endless.enemies.clear();
+        assert_eq!(catalog.validate(&EnemyCatalog::default()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut catalog = WaveCatalog::default();
+        catalog.endless.count = 0;
+        assert_eq!(catalog.validate(&EnemyCatalog::default()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // Catalogs with empty endless waves can't be loaded
+        let catalog = WaveCatalog::parse(r#"(
+            waves: [],
+            endless: (enemies: [], count: 10, count_increase: 5, spacing: 1),
+        )"#);
+        assert_eq!(catalog.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_default_wave_catalog() {
+        let catalog = WaveCatalog::default();
+
+        assert_eq!(catalog.wave(1).groups, vec![WaveGroup {
+            enemy: EnemyType::from("base"),
+            count: 10,
+            spacing: 1,
+            delay: 0,
+        }]);
+    }
+}
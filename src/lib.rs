@@ -9,57 +9,56 @@ pub use world::tower_defense_world::TowerDefenseWorld;
 
 pub mod systems;
 
-/// The total number of positions for enemies to move through
-pub const TOTAL_POSITIONS: u32 = 100;
-/// The total number of towers
-pub const TOTAL_TOWERS: u32 = 10;
-/// The separation between towers
-pub const TOWER_SEPARATION: u32 = TOTAL_POSITIONS / TOTAL_TOWERS;
-
-/// The Type of Tower
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TowerType {
-    // broken towers deal 0 units of damager per 1 second
-    Broken,
-    // the base tower deals 1 unit of damage per 1 second
-    Base,
-    // The second tower deals 1 unit of damage per 0.5 seconds
-    Second,
-    // The third tower deals 3 units of damage per 1 second
-    Third,
-    // The forth tower deals 2 units of damage per 0.5 seconds
-    Fourth,
-    // The fifth tower deals 3 units of damage per 0.5 seconds
-    Fifth,
-    // The final tower deals 3 units of damage per 0.25 seconds
-    Final,
+pub mod catalog;
+
+pub mod simulation;
+
+pub mod rng;
+
+use serde::{Deserialize, Serialize};
+
+/// The most towers that can be built at once unless configured otherwise
+pub const DEFAULT_MAX_TOWERS: u32 = 10;
+/// The percentage of the points invested in a tower refunded when it is sold unless configured otherwise
+pub const DEFAULT_REFUND_PERCENT: u32 = 75;
+/// The health of the base at the start of a game
+pub const STARTING_BASE_HEALTH: u32 = 100;
+/// The seed for the random number generator unless configured otherwise
+pub const DEFAULT_SEED: u64 = 0;
+/// The game time (in milliseconds) between runs of systems that update every tick
+pub const TICK_MILLISECONDS: u32 = 50;
+
+/// The Phase of the Game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamePhase {
+    // Enemies are spawning and moving towards the base
+    Playing,
+    // The simulation is frozen until the game is resumed
+    Paused,
+    // The base has been destroyed
+    GameOver,
 }
 
+/// The Type of Tower (the name of its definition in the tower catalog)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TowerType(pub String);
+
 impl TowerType {
-    /// The cost to upgrade a tower
-    pub fn upgrade_price(&self) -> u32 {
-        match self {
-            TowerType::Broken => 10,
-            TowerType::Base => 20,
-            TowerType::Second => 30,
-            TowerType::Third => 40,
-            TowerType::Fourth => 50,
-            TowerType::Fifth => 60,
-            TowerType::Final => 0,
-        }
+    /// The type of a tower that hasn't been built yet
+    pub fn broken() -> Self {
+        Self::from("broken")
     }
+}
 
-    /// The number of points gained from selling a tower
-    pub fn sell_price(&self) -> u32 {
-        match self {
-            TowerType::Broken => 0,
-            _ => 5,
-        }
+impl From<&str> for TowerType {
+    fn from(name: &str) -> Self {
+        Self(String::from(name))
     }
 }
 
 /// Target Enemy for a given tower
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TowerTarget {
     // The first enemy
     First,
@@ -67,21 +66,197 @@ pub enum TowerTarget {
     Second,
     // The last enemy
     Last,
+    // The enemy with the most health
+    Strongest,
+    // The enemy with the least health
+    Weakest,
+    // The enemy with the highest speed
+    Fastest,
+    // The enemy nearest the base (in a straight line)
+    Closest,
 }
 
-/// The Type of Enemy
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum EnemyType {
-    // the base enemy moves 1 unit per second and has a base health of 1
-    Base,
-    // The second class of enemy that moves 2 units per second and has a base health of 2
-    Second,
-    // The third class of enemy that moves 2 units per second and has a base health of 4
-    Third,
-    // The forth class of enemy that moves 3 units per second and has a base health of 4
-    Fourth,
-    // The fifth class of enemy that moves 3 units per second and has a base health of 6
-    Fifth,
-    // The final class of enemy that moves 1 unit per second and has a base health of 20
-    Final,
-}
\ No newline at end of file
+/// The layer an enemy moves along the path in (towers can only hit some layers)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layer {
+    // Walking along the path
+    #[default]
+    Ground,
+    // Flying above the path
+    Air,
+}
+
+/// The kind of damage a tower deals (each kind is mitigated differently)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DamageType {
+    // Reduced by an enemy's armor
+    #[default]
+    Physical,
+    // Reduced by an enemy's resistance
+    Magic,
+    // Ignores armor and resistance
+    Pierce,
+}
+
+/// The damage (already reduced by the enemy's armor or resistance when each hit landed) and healing
+/// an entity takes when health changes are next resolved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthChange {
+    pub damage: u32,
+    pub healing: u32,
+}
+
+impl HealthChange {
+    /// A health change of some damage
+    pub fn damage(damage: u32) -> Self {
+        Self { damage, ..Default::default() }
+    }
+}
+
+/// A status effect that changes how an enemy moves (or hurts it) for a while
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusEffect {
+    // The enemy moves slower by a percentage of its speed for a duration (in milliseconds)
+    Slow { percent: u32, duration: u32 },
+    // The enemy can't move for a duration (in milliseconds)
+    Freeze { duration: u32 },
+    // The enemy loses an amount of health every second for a duration (in milliseconds)
+    Poison { damage: u32, duration: u32 },
+}
+
+impl StatusEffect {
+    /// The percentage of its speed an affected enemy moves at
+    pub fn speed_percent(&self) -> u32 {
+        match self {
+            Self::Slow { percent, .. } => 100 - (*percent).min(100),
+            Self::Freeze { .. } => 0,
+            Self::Poison { .. } => 100,
+        }
+    }
+
+    /// The percentage of its speed an enemy with some status effects moves at (the slowest effect decides)
+    pub fn combined_speed_percent(status_effects: &[StatusEffect]) -> u32 {
+        status_effects.iter().map(|v| v.speed_percent()).min().unwrap_or(100)
+    }
+
+    /// The damage the effect deals while counting down by some time (in milliseconds),
+    /// dealt each time a whole second of the effect's duration runs out
+    pub fn poison_damage(&self, milliseconds: u32) -> u32 {
+        match self {
+            Self::Poison { damage, duration } => {
+                let seconds_run_out = duration.div_ceil(1000) - duration.saturating_sub(milliseconds).div_ceil(1000);
+                damage * seconds_run_out
+            },
+            _ => 0,
+        }
+    }
+
+    /// Count down the effect by some time (in milliseconds), returning None once it wears off
+    pub fn tick(self, milliseconds: u32) -> Option<Self> {
+        let effect = match self {
+            Self::Slow { percent, duration } => Self::Slow { percent, duration: duration.saturating_sub(milliseconds) },
+            Self::Freeze { duration } => Self::Freeze { duration: duration.saturating_sub(milliseconds) },
+            Self::Poison { damage, duration } => Self::Poison { damage, duration: duration.saturating_sub(milliseconds) },
+        };
+        match effect {
+            Self::Slow { duration: 0, .. } | Self::Freeze { duration: 0 } | Self::Poison { duration: 0, .. } => None,
+            effect => Some(effect),
+        }
+    }
+
+    /// Whether two effects are the same kind of effect
+    pub fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Whether the effect stacks with effects of the same kind (instead of replacing them)
+    pub fn stacks(&self) -> bool {
+        matches!(self, Self::Poison { .. })
+    }
+}
+
+/// A shield granted to an enemy by a nearby support enemy that absorbs damage until it wears off
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemporaryShield {
+    // The damage the shield absorbs
+    pub amount: u32,
+    // The time (in milliseconds) until the shield wears off
+    pub duration: u32,
+}
+
+impl TemporaryShield {
+    /// Count down the shield by some time (in milliseconds), removing it once it wears off
+    pub fn tick(self, milliseconds: u32) -> Self {
+        match self.duration.saturating_sub(milliseconds) {
+            0 => Self::default(),
+            duration => Self { amount: self.amount, duration },
+        }
+    }
+
+    /// Refresh the shield with a newly granted shield (keeping the larger amount)
+    pub fn refresh(&mut self, amount: u32, duration: u32) {
+        self.amount = self.amount.max(amount);
+        self.duration = self.duration.max(duration);
+    }
+}
+
+/// A shot fired by a tower that flies at its target and hits it once it arrives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Projectile {
+    // The type of tower that fired the shot (dealing its damage, splash, and status effect on a hit)
+    pub tower_type: TowerType,
+    // The enemy the shot is flying at (None once the target has died and the shot will miss)
+    pub target: Option<usize>,
+    // The map tile the shot was fired from
+    pub origin: (u32, u32),
+    // The map tile the target was last seen on
+    pub target_tile: (u32, u32),
+    // The distance (in thousandths of a tile) the shot has flown
+    pub travelled: u32,
+}
+
+impl Projectile {
+    /// The distance (in thousandths of a tile) from where the shot was fired to its target
+    pub fn distance(&self) -> u32 {
+        let x = self.origin.0.abs_diff(self.target_tile.0) as f64;
+        let y = self.origin.1.abs_diff(self.target_tile.1) as f64;
+        (x.hypot(y) * 1000.0) as u32
+    }
+
+    /// The fraction of the distance to its target the shot has flown
+    pub fn progress(&self) -> f64 {
+        match self.distance() {
+            0 => 1.0,
+            distance => (self.travelled as f64 / distance as f64).min(1.0),
+        }
+    }
+}
+
+/// The points paid out at the end of a wave
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaveIncome {
+    // The interest paid on banked points
+    pub interest: u32,
+    // The bonus for clearing the wave without the base taking damage
+    pub clear_bonus: u32,
+    // The income generated by towers
+    pub tower_income: u32,
+}
+
+impl WaveIncome {
+    /// The total points paid out
+    pub fn total(&self) -> u32 {
+        self.interest + self.clear_bonus + self.tower_income
+    }
+}
+
+/// The Type of Enemy (the name of its definition in the enemy catalog)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EnemyType(pub String);
+
+impl From<&str> for EnemyType {
+    fn from(name: &str) -> Self {
+        Self(String::from(name))
+    }
+}
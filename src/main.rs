@@ -2,33 +2,31 @@
 //! Tower Defense Game Built with a tui using my game engine
 //! 
 
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use nate_engine::Engine;
 
 use clap::Parser;
 
 use td_tui::systems::tower_defense::{
     alive_enemies::{count_alive_enemies, remove_dead_entities, spawn_more_enemies},
-    movement::{
-        base_enemy_movement_system,
-        second_enemy_movement_system,
-        third_enemy_movement_system,
-        fourth_enemy_movement_system,
-        fifth_enemy_movement_system,
-        final_enemy_movement_system,
-    },
-    tower::{
-        base_tower_attack_ai,
-        second_tower_attack_ai,
-        third_tower_attack_ai,
-        fourth_tower_attack_ai,
-        fifth_tower_attack_ai,
-        final_tower_attack_ai,
-        upgrade_tower,
-        downgrade_tower,
-    },
+    boss::boss_phases,
+    damage::resolve_health_change,
+    game_state::{check_game_over, restart_game},
+    movement::enemy_movement_system,
+    projectile::move_projectiles,
+    status_effect::{expire_status_effects, poison_damage},
+    stealth::detect_stealth_enemies,
+    support::{expire_temporary_shields, support_enemies},
+    tower::{tower_attack_ai, upgrade_tower, downgrade_tower, demolish_tower},
 };
 
-use td_tui::{TOTAL_TOWERS, TowerTarget, TowerDefenseWorld, tui::TowerDefenseRenderer};
+use td_tui::{DEFAULT_MAX_TOWERS, DEFAULT_REFUND_PERCENT, DEFAULT_SEED, STARTING_BASE_HEALTH, TICK_MILLISECONDS, TowerDefenseWorld, tui::TowerDefenseRenderer};
+use td_tui::catalog::{enemy_catalog::EnemyCatalog, tower_catalog::TowerCatalog, wave_catalog::WaveCatalog};
+use td_tui::rng::Rng;
+use td_tui::simulation::{Simulation, SimulationScript};
+use td_tui::world::map::Map;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -40,6 +38,53 @@ struct Args {
     // Multiplier of the base game speed to play at
     #[arg(short, long, default_value_t = 0.5)]
     multiplier: f32,
+
+    // Tower catalog file to load instead of the default towers
+    #[arg(short, long)]
+    towers: Option<PathBuf>,
+
+    // Enemy catalog file to load instead of the default enemies
+    #[arg(short, long)]
+    enemies: Option<PathBuf>,
+
+    // Wave file to load instead of the default waves
+    #[arg(long)]
+    waves: Option<PathBuf>,
+
+    // Map file to play on instead of the default map
+    #[arg(long)]
+    map: Option<PathBuf>,
+
+    // Most towers that can be built at once
+    #[arg(long, default_value_t = DEFAULT_MAX_TOWERS)]
+    max_towers: u32,
+
+    // Percentage of the points invested in a tower refunded when it is sold or demolished (at most 100)
+    #[arg(long, default_value_t = DEFAULT_REFUND_PERCENT, value_parser = clap::value_parser!(u32).range(0..=100))]
+    refund_percent: u32,
+
+    // Seed for critical hits and dodges, picked from the clock unless given (simulations use a fixed
+    // seed instead). Only simulations replay exactly: in the terminal, systems drawing random numbers
+    // run concurrently, so the order of the draws depends on thread scheduling
+    #[arg(long)]
+    seed: Option<u64>,
+
+    // Saved game to continue instead of starting a new game (the game keeps the catalogs, map,
+    // and settings it was saved with, so they can't be given as well)
+    #[arg(long, conflicts_with_all = ["towers", "enemies", "waves", "map", "max_towers", "refund_percent", "seed"])]
+    load: Option<PathBuf>,
+
+    // File the game is saved to
+    #[arg(long, default_value = "td-tui-save.ron")]
+    save: PathBuf,
+
+    // Script of player actions to simulate without a terminal (prints a report of the game)
+    #[arg(long)]
+    simulate: Option<PathBuf>,
+
+    // Maximum number of ticks to simulate
+    #[arg(long, default_value_t = 72_000)]
+    max_ticks: u64,
 }
 
 fn main() -> std::io::Result<()> {
@@ -49,20 +94,51 @@ fn main() -> std::io::Result<()> {
     {
         let mut world = world.write().unwrap();
 
-        // Initialize Singular Components
-        world.initialize_singular_components(100);
+        world.set_max_towers(args.max_towers);
+        world.set_refund_percent(args.refund_percent);
+        world.set_rng(Rng::new(args.seed.unwrap_or_else(|| match args.simulate {
+            Some(_) => DEFAULT_SEED,
+            None => SystemTime::now().duration_since(UNIX_EPOCH).map(|v| v.as_nanos() as u64).unwrap_or(DEFAULT_SEED),
+        })));
 
-        // Add a real tower
-        world.add_base_tower(TowerTarget::First, 0);
+        // Load the tower, enemy, and wave catalogs (making sure the waves only spawn known enemies) and the map
+        if let Some(towers) = args.towers.as_ref() {
+            world.set_tower_catalog(TowerCatalog::from_file(towers)?);
+        }
+        let enemy_catalog = match args.enemies.as_ref() {
+            Some(enemies) => EnemyCatalog::from_file(enemies)?,
+            None => EnemyCatalog::default(),
+        };
+        let wave_catalog = match args.waves.as_ref() {
+            Some(waves) => WaveCatalog::from_file(waves)?,
+            None => WaveCatalog::default(),
+        };
+        wave_catalog.validate(&enemy_catalog)?;
+        world.set_enemy_catalog(enemy_catalog);
+        world.set_wave_catalog(wave_catalog);
+        if let Some(map) = args.map.as_ref() {
+            world.set_map(Map::from_file(map)?);
+        }
 
-        // Add Broken Towers
-        world.add_broken_towers((TOTAL_TOWERS - 1) as usize);
+        // Continue a saved game or add the starting towers and enemies
+        if let Some(load) = args.load.as_ref() {
+            world.load_game(load)?;
+        } else {
+            world.new_game(STARTING_BASE_HEALTH);
+        }
+    }
 
-        // Add some enemies
-        world.add_base_enemies(vec![99, 98, 97, 96, 95, 94, 93, 92, 91, 90]);
+    // Run a headless simulation instead of the game
+    if let Some(simulate) = args.simulate.as_ref() {
+        let script = SimulationScript::from_file(simulate)?;
+        let report = Simulation::new(world).run(&script, args.max_ticks);
+        let report = ron::ser::to_string_pretty(&report, ron::ser::PrettyConfig::default())
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string()))?;
+        println!("{}", report);
+        return Ok(());
     }
 
-    let renderer = TowerDefenseRenderer::new()?;
+    let renderer = TowerDefenseRenderer::new(args.save)?;
 
     let mut engine = Engine::new(
         60,
@@ -71,21 +147,22 @@ fn main() -> std::io::Result<()> {
         vec![
             (count_alive_enemies, 100_000),
             (remove_dead_entities, 100_000),
-            (base_enemy_movement_system, (1_000_000.0 * args.multiplier) as u128),
-            (second_enemy_movement_system, (1_000_000.0 * args.multiplier) as u128),
-            (third_enemy_movement_system, (1_000_000.0 * args.multiplier) as u128),
-            (fourth_enemy_movement_system, (1_000_000.0 * args.multiplier) as u128),
-            (fifth_enemy_movement_system, (1_000_000.0 * args.multiplier) as u128),
-            (final_enemy_movement_system, (1_000_000.0 * args.multiplier) as u128),
-            (base_tower_attack_ai, (1_000_000.0 * args.multiplier) as u128),
-            (second_tower_attack_ai, (500_000.0 * args.multiplier) as u128),
-            (third_tower_attack_ai, (1_000_000.0 * args.multiplier) as u128),
-            (fourth_tower_attack_ai, (500_000.0 * args.multiplier) as u128),
-            (fifth_tower_attack_ai, (500_000.0 * args.multiplier) as u128),
-            (final_tower_attack_ai, (250_000.0 * args.multiplier) as u128),
+            (enemy_movement_system, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (support_enemies, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (expire_temporary_shields, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (poison_damage, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (expire_status_effects, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (detect_stealth_enemies, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (tower_attack_ai, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (move_projectiles, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (resolve_health_change, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
+            (boss_phases, (TICK_MILLISECONDS as f32 * 1_000.0 * args.multiplier) as u128),
             (upgrade_tower, 50_000),
             (downgrade_tower, 50_000),
+            (demolish_tower, 50_000),
             (spawn_more_enemies, 1_000_000),
+            (check_game_over, 100_000),
+            (restart_game, 50_000),
         ],
         Box::new(renderer)
     );
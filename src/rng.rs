@@ -0,0 +1,75 @@
+//!
+//! Seeded Random Number Generation
+//! 
+
+use serde::{Deserialize, Serialize};
+
+/// A small seeded random number generator (SplitMix64), so simulated games with the same seed
+/// (and the same inputs) always play out the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+    // The seed the generator started from
+    seed: u64,
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// A generator starting over from the same seed
+    pub fn restarted(&self) -> Self {
+        Self::new(self.seed)
+    }
+
+    /// The next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Randomly succeed a percentage of the time (certain chances don't use up a number)
+    pub fn chance(&mut self, percent: u32) -> bool {
+        match percent {
+            0 => false,
+            100.. => true,
+            percent => self.next_u64() % 100 < percent as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_gives_same_numbers() {
+        let mut first = Rng::new(42);
+        let mut second = Rng::new(42);
+        for _ in 0..10 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+
+        // Restarting replays the same numbers
+        let mut restarted = first.restarted();
+        assert_eq!(restarted, Rng::new(42));
+        assert_eq!(restarted.next_u64(), Rng::new(42).next_u64());
+    }
+
+    #[test]
+    fn test_chance() {
+        let mut rng = Rng::new(7);
+        assert!(!rng.chance(0));
+        assert!(rng.chance(100));
+        assert_eq!(rng, Rng::new(7));
+
+        // Roughly a quarter of rolls succeed
+        let successes = (0..1000).filter(|_| rng.chance(25)).count();
+        assert!((200..300).contains(&successes));
+    }
+}
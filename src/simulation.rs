@@ -0,0 +1,258 @@
+//!
+//! Headless Simulation of Tower Defense Games
+//! 
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GamePhase, TowerDefenseWorld, TowerTarget};
+use crate::systems::tower_defense::{
+    alive_enemies::{count_alive_enemies, remove_dead_entities, spawn_more_enemies},
+    boss::boss_phases,
+    damage::resolve_health_change,
+    game_state::check_game_over,
+    movement::enemy_movement_system,
+    projectile::move_projectiles,
+    status_effect::{expire_status_effects, poison_damage},
+    stealth::detect_stealth_enemies,
+    support::{expire_temporary_shields, support_enemies},
+    tower::{demolish_tower, downgrade_tower, tower_attack_ai, upgrade_tower},
+};
+
+/// A system that can be stepped by the simulation
+pub type TickSystem = fn(Arc<RwLock<TowerDefenseWorld>>);
+
+/// The systems run every tick, in the order they are run
+pub const TICK_SYSTEMS: [TickSystem; 14] = [
+    enemy_movement_system,
+    support_enemies,
+    expire_temporary_shields,
+    poison_damage,
+    expire_status_effects,
+    detect_stealth_enemies,
+    tower_attack_ai,
+    move_projectiles,
+    resolve_health_change,
+    boss_phases,
+    remove_dead_entities,
+    count_alive_enemies,
+    spawn_more_enemies,
+    check_game_over,
+];
+
+/// An input a player can make
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerAction {
+    // Move the placement cursor to a map tile
+    MoveCursor(u32, u32),
+    // Set the enemy the tower under the cursor targets
+    SetTarget(TowerTarget),
+    // Upgrade the tower under the cursor (or build a tower on a free buildable tile)
+    Upgrade,
+    // Upgrade the tower under the cursor into one of its branch towers, counting from 0 (or build
+    // one on a free buildable tile)
+    Branch(usize),
+    // Sell the tower under the cursor back into the tower it was upgraded from
+    Sell,
+    // Demolish the tower under the cursor straight back into a broken tower
+    Demolish,
+}
+
+/// A player action made at a given tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptedAction {
+    // The tick the action is made at (before the systems run)
+    pub tick: u64,
+    pub action: PlayerAction,
+}
+
+/// The player actions made during a simulated game
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationScript {
+    pub actions: Vec<ScriptedAction>,
+}
+
+impl SimulationScript {
+    /// Parse a simulation script from the contents of a script file
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        ron::from_str(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    /// Load a simulation script from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+/// The outcome of a simulated game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    // The number of ticks simulated
+    pub ticks: u64,
+    // Whether the base was destroyed
+    pub game_over: bool,
+    pub level: u32,
+    pub points: u32,
+    pub base_health: u32,
+    pub enemies_killed: u32,
+    // The damage dealt to enemies beyond what it took to kill them
+    pub overkill_damage: u32,
+}
+
+/// Steps a world one tick at a time without a terminal
+pub struct Simulation {
+    world: Arc<RwLock<TowerDefenseWorld>>,
+    // The number of ticks simulated so far
+    tick: u64,
+}
+
+impl Simulation {
+    pub fn new(world: Arc<RwLock<TowerDefenseWorld>>) -> Self {
+        Self {
+            world,
+            tick: 0,
+        }
+    }
+
+    /// Make a player action the same way the renderer does
+    pub fn apply_action(&self, action: PlayerAction) {
+        {
+            let world = self.world.read().unwrap();
+            match action {
+                PlayerAction::MoveCursor(x, y) => *world.cursor.write().unwrap() = Some((x, y)),
+                PlayerAction::SetTarget(target) => world.set_selected_tower_target(target),
+                PlayerAction::Upgrade => *world.upgrading_tower.write().unwrap() = Some(true),
+                PlayerAction::Branch(branch) => *world.branching_tower.write().unwrap() = Some(Some(branch)),
+                PlayerAction::Sell => *world.downgrading_tower.write().unwrap() = Some(true),
+                PlayerAction::Demolish => *world.demolishing_tower.write().unwrap() = Some(true),
+            }
+        }
+
+        // Handle the input straight away so several actions can be made in one tick
+        upgrade_tower(self.world.clone());
+        downgrade_tower(self.world.clone());
+        demolish_tower(self.world.clone());
+    }
+
+    /// Run every tick system once
+    pub fn step(&mut self) {
+        for system in TICK_SYSTEMS {
+            system(self.world.clone());
+        }
+        self.tick += 1;
+    }
+
+    /// Simulate the game until the base is destroyed or the tick limit is reached
+    pub fn run(&mut self, script: &SimulationScript, max_ticks: u64) -> SimulationReport {
+        let mut actions = script.actions.clone();
+        actions.sort_by_key(|v| v.tick);
+        let mut actions = actions.into_iter().peekable();
+
+        while self.tick < max_ticks && !self.game_over() {
+            while let Some(action) = actions.next_if(|v| v.tick <= self.tick) {
+                self.apply_action(action.action);
+            }
+            self.step();
+        }
+
+        self.report()
+    }
+
+    /// Whether the base has been destroyed
+    pub fn game_over(&self) -> bool {
+        *self.world.read().unwrap().game_phase.read().unwrap() == Some(GamePhase::GameOver)
+    }
+
+    /// The current state of the simulated game
+    pub fn report(&self) -> SimulationReport {
+        let world = self.world.read().unwrap();
+        let report = SimulationReport {
+            ticks: self.tick,
+            game_over: *world.game_phase.read().unwrap() == Some(GamePhase::GameOver),
+            level: world.level.read().unwrap().expect("Level must be initialized"),
+            points: world.points.read().unwrap().expect("Points must be initialized"),
+            base_health: world.base_health.read().unwrap().expect("Base Health must be initialized"),
+            enemies_killed: world.enemies_killed.read().unwrap().expect("Enemies Killed must be initialized"),
+            overkill_damage: world.overkill_damage.read().unwrap().expect("Overkill Damage must be initialized"),
+        };
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::STARTING_BASE_HEALTH;
+
+    /// Start a new game in a fresh world
+    fn new_simulation() -> Simulation {
+        let world = TowerDefenseWorld::new();
+        world.write().unwrap().new_game(STARTING_BASE_HEALTH);
+        Simulation::new(world)
+    }
+
+    #[test]
+    fn test_simulation_is_deterministic() {
+        let script = SimulationScript::parse(r#"(
+            actions: [
+                (tick: 0, action: MoveCursor(10, 2)),
+                (tick: 0, action: Upgrade),
+                (tick: 200, action: SetTarget(Last)),
+            ],
+        )"#).unwrap();
+
+        let first = new_simulation().run(&script, 10_000);
+        let second = new_simulation().run(&script, 10_000);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_simulation_stops_at_game_over() {
+        let report = new_simulation().run(&SimulationScript::default(), 1_000_000);
+
+        assert!(report.game_over);
+        assert_eq!(report.base_health, 0);
+        assert!(report.ticks < 1_000_000);
+    }
+
+    #[test]
+    fn test_simulation_stops_at_max_ticks() {
+        let report = new_simulation().run(&SimulationScript::default(), 10);
+
+        assert!(!report.game_over);
+        assert_eq!(report.ticks, 10);
+        assert_eq!(report.level, 1);
+        assert_eq!(report.base_health, STARTING_BASE_HEALTH);
+    }
+
+    #[test]
+    fn test_simulation_applies_several_actions_in_one_tick() {
+        let mut simulation = new_simulation();
+        simulation.world.write().unwrap().set_points(30);
+
+        let script = SimulationScript {
+            actions: vec![
+                ScriptedAction { tick: 0, action: PlayerAction::MoveCursor(10, 2) },
+                ScriptedAction { tick: 0, action: PlayerAction::Upgrade },
+                ScriptedAction { tick: 0, action: PlayerAction::MoveCursor(20, 2) },
+                ScriptedAction { tick: 0, action: PlayerAction::Upgrade },
+            ],
+        };
+        let report = simulation.run(&script, 1);
+
+        // Check that both towers were built
+        let read_world = simulation.world.read().unwrap();
+        let built_towers = read_world.tower_type.read().unwrap().iter()
+            .filter(|v| v.as_ref().is_some_and(|v| v.0 == "base"))
+            .count();
+        assert_eq!(built_towers, 3);
+        assert_eq!(report.points, 10);
+    }
+}
@@ -5,3 +5,10 @@
 pub mod movement;
 pub mod alive_enemies;
 pub mod tower;
+pub mod projectile;
+pub mod damage;
+pub mod status_effect;
+pub mod boss;
+pub mod support;
+pub mod stealth;
+pub mod game_state;
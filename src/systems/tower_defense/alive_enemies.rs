@@ -8,9 +8,8 @@ use std::sync::{Arc, RwLock};
 
 use nate_engine::system;
 
-use crate::{EnemyType, TowerDefenseWorld, TOTAL_POSITIONS};
-
-// (0, 10) -> (0, 20) -> (10, 0) -> (10, 10) -> (10, 20) -> (20, 0)
+use crate::{EnemyType, GamePhase, TowerDefenseWorld};
+use crate::catalog::enemy_catalog::DeathAbility;
 
 #[system(
     world=TowerDefenseWorld,
@@ -23,28 +22,71 @@ fn count_alive_enemies() {
     }
 }
 
+/// An enemy that died this tick
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnemyDeath {
+    pub enemy_type: EnemyType,
+    // The number of path tiles the enemy had left to walk to reach the base
+    pub enemy_position: u32,
+    // Whether the enemy was killed (rather than reaching the base)
+    pub killed: bool,
+}
+
+/// Use the abilities of an enemy that has died (after it has been removed from the world)
+pub fn on_enemy_death(world: &mut TowerDefenseWorld, death: &EnemyDeath) {
+    // Enemies that reached the base don't use their abilities
+    if !death.killed {
+        return;
+    }
+
+    let on_death = world.enemy_catalog.read().unwrap().as_ref()
+        .expect("Enemy Catalog must be loaded")
+        .get(&death.enemy_type)
+        .on_death
+        .clone();
+    for ability in on_death {
+        match ability {
+            DeathAbility::Split { enemy, count } => {
+                let _ = world.add_catalog_enemies(enemy, vec![death.enemy_position; count as usize]);
+            },
+        }
+    }
+}
+
+/// Remove every zero health entity (and every entity queued for removal), award the bounty of
+/// killed enemies, and then let the dead enemies use their death abilities
 pub fn remove_dead_entities(world: Arc<RwLock<TowerDefenseWorld>>) {
     let mut remove_entities = Vec::new();
+    let mut deaths = Vec::new();
     {
         let read_world = world.read().unwrap();
         
         // Check for 0 health entities
         let health = read_world.health.read().unwrap();
         let enemy_type = read_world.enemy_type.read().unwrap();
+        let enemy_position = read_world.enemy_position.read().unwrap();
+        let enemy_catalog_ref = read_world.enemy_catalog.read().unwrap();
+        let enemy_catalog = enemy_catalog_ref.as_ref().unwrap();
         let mut points_ref = read_world.points.write().unwrap();
         let mut points = points_ref.as_mut().unwrap();
-        for ((entity_id, health), enemy_type) in health.iter().enumerate().zip(enemy_type.iter()).rev() {
+        let mut enemies_killed_ref = read_world.enemies_killed.write().unwrap();
+        let enemies_killed = enemies_killed_ref.as_mut().unwrap();
+        for (((entity_id, health), enemy_type), enemy_position) in health.iter().enumerate().zip(enemy_type.iter()).zip(enemy_position.iter()).rev() {
             if let Some(health) = health.as_ref() {
                 if *health == 0 {
                     remove_entities.push(entity_id);
-                    match enemy_type.unwrap() {
-                        EnemyType::Base => *points += 1,
-                        EnemyType::Second => *points += 2,
-                        EnemyType::Third => *points += 3,
-                        EnemyType::Fourth => *points += 4,
-                        EnemyType::Fifth => *points += 5,
-                        EnemyType::Final => *points += 10,
+                    // Enemies that reached the base were not killed by a tower
+                    let killed = enemy_position.is_some_and(|v| v > 0);
+                    // Enemies only pay their bounty when they are killed (not for leaking)
+                    if killed {
+                        *enemies_killed += 1;
+                        *points += enemy_catalog.get(enemy_type.as_ref().unwrap()).bounty;
                     }
+                    deaths.push(EnemyDeath {
+                        enemy_type: enemy_type.clone().unwrap(),
+                        enemy_position: enemy_position.unwrap_or(0),
+                        killed,
+                    });
                 }
             }
         }
@@ -52,47 +94,38 @@ pub fn remove_dead_entities(world: Arc<RwLock<TowerDefenseWorld>>) {
 
     // Remove entities from the remove entities list
     let mut write_world = world.write().unwrap();
-    write_world.remove_entities(remove_entities);
+    let removal_entities = write_world.removal_entities.write().unwrap().replace(Vec::new());
+    remove_entities.extend(removal_entities.unwrap_or_default());
+    write_world.despawn_entities(remove_entities);
+
+    // Handle the deaths in the order the enemies were added
+    for death in deaths.iter().rev() {
+        on_enemy_death(&mut write_world, death);
+    }
 }
 
-/// If a level has been completed, increment the level and add some new enemies
+/// If a level has been completed, pay out the wave's income (interest, the clear bonus, and
+/// tower income), increment the level, and spawn the wave for the next level
 pub fn spawn_more_enemies(world: Arc<RwLock<TowerDefenseWorld>>) {
+    if *world.read().unwrap().game_phase.read().unwrap() != Some(GamePhase::Playing) {
+        return;
+    }
+
     if *world.read().unwrap().alive_enemies.read().unwrap() == Some(0) {
         // Increment Level
         let next_level = {
             let world = world.read().unwrap();
 
+            let wave_income = world.wave_income();
+            *world.points.write().unwrap().as_mut().unwrap() += wave_income.total();
+
             let mut level_ref = world.level.write().unwrap();
             *level_ref.as_mut().unwrap() += 1;
             *level_ref.as_ref().unwrap()
         };
 
-        // Add enemies
-        {
-            let mut world = world.write().unwrap();
-
-            // Add enemies
-            let level_one_enemies = (next_level % 3) * 10;
-            let level_two_enemies = ((next_level / 3) % 3) * 10;
-            let level_three_enemies = ((next_level / 6) % 3) * 10;
-            let level_four_enemies = ((next_level / 9) % 3) * 10;
-            let level_five_enemies = ((next_level / 12) % 3) * 10;
-            let level_six_enemies = ((next_level / 15) % 3) * 2;
-
-            let level_one_positions = (0..level_one_enemies).map(|v| TOTAL_POSITIONS + v).collect();
-            let level_two_positions = (0..level_two_enemies).map(|v| TOTAL_POSITIONS + level_one_enemies + v).collect();
-            let level_three_positions = (0..level_three_enemies).map(|v| TOTAL_POSITIONS + level_one_enemies + level_two_enemies + v).collect();
-            let level_four_positions = (0..level_four_enemies).map(|v| TOTAL_POSITIONS + level_one_enemies + level_two_enemies + level_three_enemies + v).collect();
-            let level_five_positions = (0..level_four_enemies).map(|v| TOTAL_POSITIONS + level_one_enemies + level_two_enemies + level_three_enemies + level_four_enemies + v).collect();
-            let level_six_positions = (0..level_six_enemies).map(|v| TOTAL_POSITIONS + level_one_enemies + level_two_enemies + v).collect();
-
-            world.add_base_enemies(level_one_positions);
-            world.add_second_enemies(level_two_positions);
-            world.add_third_enemies(level_three_positions);
-            world.add_fourth_enemies(level_four_positions);
-            world.add_fifth_enemies(level_five_positions);
-            world.add_final_enemies(level_six_positions);
-        }
+        // Add the enemies in the wave for the next level
+        world.write().unwrap().spawn_wave(next_level);
     }
 }
 
@@ -100,6 +133,8 @@ pub fn spawn_more_enemies(world: Arc<RwLock<TowerDefenseWorld>>) {
 mod tests {
     use super::*;
 
+    use crate::{TowerTarget, TowerType};
+
     #[test]
     fn test_alive_enemies_counts_alive_enemies() {
         let world = TowerDefenseWorld::new();
@@ -111,8 +146,8 @@ mod tests {
             world.initialize_singular_components(100);
 
             // Spawn 100 enemies
-            let positions = (1..101).map(|v| v).collect();
-            let _ = world.add_base_enemies(positions);
+            let positions = (1..101).collect();
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), positions);
         }
 
         count_alive_enemies(world.clone());
@@ -134,7 +169,7 @@ mod tests {
 
             // Spawn 100 Enemies with 0 health
             let _ = world.add_enemies(
-                (0..100).map(|_v| crate::EnemyType::Base).collect(),
+                (0..100).map(|_v| EnemyType::from("base")).collect(),
                 (0..100).map(|_v| String::from("X")).collect(),
                 (1..101).collect(),
                 (0..100).map(|_v| 0).collect(),
@@ -160,7 +195,7 @@ mod tests {
 
             // Spawn 100 Enemies with 0 health
             let _ = world.add_enemies(
-                (0..100).map(|_v| crate::EnemyType::Base).collect(),
+                (0..100).map(|_v| EnemyType::from("base")).collect(),
                 (0..100).map(|_v| String::from("X")).collect(),
                 (1..101).collect(),
                 (0..100).map(|_v| 0).collect(),
@@ -223,10 +258,53 @@ mod tests {
 
         // Make sure 20 level 1 enemies were instantiated
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.is_some() && v.unwrap() == EnemyType::Base).count(), 20);
+        assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.as_ref() == Some(&EnemyType::from("base"))).count(), 20);
+        assert_eq!(*read_world.level.read().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_spawn_enemies_pays_wave_income() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize singular components with 100 banked points, a bank, and a base tower
+            world.initialize_singular_components(100);
+            world.set_points(100);
+            let _ = world.add_catalog_tower(TowerType::from("bank"), TowerTarget::First, (0, 1));
+            let _ = world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (1, 1));
+        }
+
+        spawn_more_enemies(world.clone());
+
+        // Make sure 5 points of interest, the 5 point clear bonus, and 8 points from the bank were paid
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.points.read().unwrap(), Some(118));
         assert_eq!(*read_world.level.read().unwrap(), Some(2));
     }
 
+    #[test]
+    fn test_spawn_enemies_no_clear_bonus_after_base_damage() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize singular components and damage the base during the wave
+            world.initialize_singular_components(100);
+            world.set_points(100);
+            world.set_base_health(90);
+        }
+
+        spawn_more_enemies(world.clone());
+
+        // Make sure only interest was paid and the next wave starts from the damaged base health
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.points.read().unwrap(), Some(105));
+        assert_eq!(*read_world.wave_start_base_health.read().unwrap(), Some(90));
+    }
+
     #[test]
     fn test_spawn_enemies_level_7() {
         let world = TowerDefenseWorld::new();
@@ -243,8 +321,155 @@ mod tests {
 
         // Make sure 10 level 1 enemies and 20 level 2 enemies were instantiated
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.is_some() && v.unwrap() == EnemyType::Base).count(), 10);
-        assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.is_some() && v.unwrap() == EnemyType::Second).count(), 20);
+        assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.as_ref() == Some(&EnemyType::from("base"))).count(), 10);
+        assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.as_ref() == Some(&EnemyType::from("second"))).count(), 20);
         assert_eq!(*read_world.level.read().unwrap(), Some(7));
     }
+
+    #[test]
+    fn test_spawn_enemies_endless_wave() {
+        let world = TowerDefenseWorld::new();
+
+        let scripted_waves;
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize singular components and finish the last scripted level
+            world.initialize_singular_components(100);
+            scripted_waves = world.wave_catalog.read().unwrap().as_ref().unwrap().waves.len() as u32;
+            world.set_level(scripted_waves + 1);
+        }
+
+        spawn_more_enemies(world.clone());
+
+        // Make sure the second endless wave was spawned with 12 of each endless enemy
+        let read_world = world.read().unwrap();
+        for enemy in ["third", "fourth", "fifth", "final"] {
+            assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.as_ref() == Some(&EnemyType::from(enemy))).count(), 12);
+        }
+        assert_eq!(*read_world.level.read().unwrap(), Some(scripted_waves + 2));
+    }
+
+    #[test]
+    fn test_remove_dead_entities_counts_killed_enemies() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the Singular Components (with 10 starting points)
+            world.initialize_singular_components(100);
+
+            // Spawn 3 dead enemies (one of which reached the base)
+            let _ = world.add_enemies(
+                vec![EnemyType::from("base"); 3],
+                vec![String::from("X"); 3],
+                vec![0, 10, 20],
+                vec![0; 3],
+            );
+        }
+
+        remove_dead_entities(world.clone());
+
+        // Check that only the enemies killed by towers were counted and paid their bounty
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.enemies_killed.read().unwrap(), Some(2));
+        assert_eq!(*read_world.points.read().unwrap(), Some(12));
+    }
+
+    #[test]
+    fn test_remove_dead_entities_pays_boss_bounty_when_killed() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the Singular Components (with 10 starting points)
+            world.initialize_singular_components(100);
+
+            // Spawn 2 dead bosses (one of which reached the base)
+            let _ = world.add_enemies(
+                vec![EnemyType::from("boss"); 2],
+                vec![String::from("W"); 2],
+                vec![0, 10],
+                vec![0; 2],
+            );
+        }
+
+        remove_dead_entities(world.clone());
+
+        // Check that only the killed boss paid its bounty
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.points.read().unwrap(), Some(110));
+    }
+
+    #[test]
+    fn test_killed_splitter_spawns_children() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the Singular Components
+            world.initialize_singular_components(100);
+
+            // Spawn a killed splitter and a splitter that reached the base
+            let _ = world.add_enemies(
+                vec![EnemyType::from("splitter"); 2],
+                vec![String::from("Y"); 2],
+                vec![25, 0],
+                vec![0; 2],
+            );
+        }
+
+        remove_dead_entities(world.clone());
+
+        // Check that only the killed splitter split into 3 base enemies where it died
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.enemy_type.read().unwrap(), vec![Some(EnemyType::from("base")); 3]);
+        assert_eq!(*read_world.enemy_position.read().unwrap(), vec![Some(25); 3]);
+        assert_eq!(*read_world.health.read().unwrap(), vec![Some(1); 3]);
+    }
+
+    #[test]
+    fn test_spawn_enemies_game_over() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize singular components and end the game
+            world.initialize_singular_components(0);
+            world.set_game_phase(GamePhase::GameOver);
+            world.set_alive_enemies(0);
+        }
+
+        spawn_more_enemies(world.clone());
+
+        // Make sure no enemies were spawned and the level didn't change
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_type.read().unwrap().len(), 0);
+        assert_eq!(*read_world.level.read().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_spawn_enemies_paused() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize singular components and pause the game
+            world.initialize_singular_components(100);
+            world.set_game_phase(GamePhase::Paused);
+            world.set_alive_enemies(0);
+        }
+
+        spawn_more_enemies(world.clone());
+
+        // Make sure no enemies were spawned and the level didn't change
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_type.read().unwrap().len(), 0);
+        assert_eq!(*read_world.level.read().unwrap(), Some(1));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+//!
+//! Boss Systems
+//! 
+
+use std::sync::{Arc, RwLock};
+
+use crate::{EnemyType, GamePhase, TowerDefenseWorld};
+
+/// Move every boss into the phases its health has dropped to, changing its speed and
+/// shield and spawning its minions behind it
+pub fn boss_phases(world: Arc<RwLock<TowerDefenseWorld>>) {
+    if *world.read().unwrap().game_phase.read().unwrap() != Some(GamePhase::Playing) {
+        return;
+    }
+
+    // The minions to spawn (the speed and shield of each boss entering a phase are changed under
+    // the same world guard its id was found under, so it can't be despawned in between)
+    let mut minions: Vec<(EnemyType, u32)> = Vec::new();
+    {
+        let read_world = world.read().unwrap();
+
+        let health = read_world.health.read().unwrap();
+        let enemy_type = read_world.enemy_type.read().unwrap();
+        let enemy_position = read_world.enemy_position.read().unwrap();
+        let enemy_catalog_ref = read_world.enemy_catalog.read().unwrap();
+        let enemy_catalog = enemy_catalog_ref.as_ref().unwrap();
+        let mut speed = read_world.speed.write().unwrap();
+        let mut shield = read_world.shield.write().unwrap();
+        let mut boss_phase = read_world.boss_phase.write().unwrap();
+        for (entity_id, boss_phase) in boss_phase.iter_mut().enumerate() {
+            let (Some(boss_phase), Some(health), Some(enemy_type), Some(position)) = (
+                boss_phase.as_mut(),
+                health[entity_id],
+                enemy_type[entity_id].as_ref(),
+                enemy_position[entity_id],
+            ) else {
+                continue;
+            };
+            if health == 0 {
+                continue;
+            }
+
+            let definition = enemy_catalog.get(enemy_type);
+            while let Some(phase) = definition.phases.get(*boss_phase as usize) {
+                if health * 100 > definition.health * phase.health_percent {
+                    break;
+                }
+                *boss_phase += 1;
+                if let Some(phase_speed) = phase.speed {
+                    speed[entity_id] = Some(phase_speed);
+                }
+                if let Some(boss_shield) = shield[entity_id].as_mut() {
+                    *boss_shield += phase.shield;
+                }
+                minions.extend(phase.minions.iter().enumerate().map(|(i, v)| (v.clone(), position + 1 + i as u32)));
+            }
+        }
+    }
+
+    // Spawn the minions
+    if !minions.is_empty() {
+        let mut write_world = world.write().unwrap();
+        for (enemy_type, position) in minions {
+            write_world.add_catalog_enemy(enemy_type, position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boss_stays_in_first_phase_at_full_health() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a boss at full health
+            let _ = world.add_catalog_enemy(EnemyType::from("boss"), 30);
+        }
+
+        boss_phases(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.boss_phase.read().unwrap()[0], Some(0));
+        assert_eq!(read_world.enemy_type.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_boss_spawns_minions() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a boss that has lost a quarter of its health
+            let _ = world.add_enemy(EnemyType::from("boss"), String::from("W"), 30, 112);
+        }
+
+        boss_phases(world.clone());
+
+        // Make sure three minions were spawned behind the boss
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.boss_phase.read().unwrap()[0], Some(1));
+        assert_eq!(
+            *read_world.enemy_position.read().unwrap(),
+            vec![Some(30), Some(31), Some(32), Some(33)],
+        );
+        assert_eq!(read_world.enemy_type.read().unwrap()[1], Some(EnemyType::from("second")));
+    }
+
+    #[test]
+    fn test_boss_enters_every_phase_it_has_dropped_to() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a boss that has lost most of its health at once
+            let _ = world.add_enemy(EnemyType::from("boss"), String::from("W"), 30, 20);
+        }
+
+        boss_phases(world.clone());
+
+        // Make sure the boss sped up, got its shield, and spawned its minions
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.boss_phase.read().unwrap()[0], Some(3));
+        assert_eq!(read_world.speed.read().unwrap()[0], Some(2));
+        assert_eq!(read_world.shield.read().unwrap()[0], Some(30));
+        assert_eq!(read_world.enemy_type.read().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,164 @@
+//!
+//! Damage Resolution Systems
+//! 
+
+use nate_engine::system;
+
+use crate::{HealthChange, TowerDefenseWorld};
+
+/// Apply the health changes accumulated by every enemy this tick (absorbing damage with its shields
+/// and capping healing at its catalog health) and clear them
+#[system(
+    world=TowerDefenseWorld,
+    read=[enemy_type],
+    write=[health, health_change, shield, temporary_shield],
+    _write=[overkill_damage],
+    _read=[enemy_catalog],
+)]
+pub fn resolve_health_change() {
+    if *health > 0 {
+        let definition = enemy_catalog.get(enemy_type);
+        let damage = health_change.damage;
+
+        // The temporary shield and then the shield absorb damage before the enemy loses health
+        let absorbed = damage.min(temporary_shield.amount);
+        temporary_shield.amount -= absorbed;
+        let damage = damage - absorbed;
+        let absorbed = damage.min(*shield);
+        *shield -= absorbed;
+        let damage = damage - absorbed;
+
+        *overkill_damage += damage.saturating_sub(*health);
+        *health = health.saturating_sub(damage);
+
+        // Enemies killed this tick can't be healed
+        if *health > 0 {
+            let max_health = definition.health.max(*health);
+            *health = (*health + health_change.healing).min(max_health);
+        }
+    }
+
+    *health_change = HealthChange::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{EnemyType, TemporaryShield};
+
+    #[test]
+    fn test_resolve_damage_from_several_towers() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add an enemy that has been hit by two towers (for 1 and 2 damage)
+            let enemy_id = world.add_enemy(EnemyType::from("base"), String::from("X"), 10, 10);
+            world.set_health_change(enemy_id, HealthChange::damage(1 + 2));
+        }
+
+        resolve_health_change(world.clone());
+
+        // Make sure both hits were applied and the change was cleared
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health.read().unwrap()[0], Some(7));
+        assert_eq!(read_world.health_change.read().unwrap()[0], Some(HealthChange::default()));
+        assert_eq!(*read_world.overkill_damage.read().unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_resolve_damage_tracks_overkill() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Hit a base enemy (1 health) for 4 damage
+            let enemy_id = world.add_catalog_enemy(EnemyType::from("base"), 10);
+            world.set_health_change(enemy_id, HealthChange::damage(4));
+        }
+
+        resolve_health_change(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health.read().unwrap()[0], Some(0));
+        assert_eq!(*read_world.overkill_damage.read().unwrap(), Some(3));
+    }
+
+    #[test]
+    fn test_resolve_healing_up_to_catalog_health() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Heal a damaged third enemy (4 health) by more than it is missing
+            let enemy_id = world.add_enemy(EnemyType::from("third"), String::from("T"), 10, 2);
+            world.set_health_change(enemy_id, HealthChange { healing: 5, ..Default::default() });
+        }
+
+        resolve_health_change(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health.read().unwrap()[0], Some(4));
+    }
+
+    #[test]
+    fn test_resolve_damage_with_shield() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Hit a shielded enemy for more damage than its shield absorbs
+            let enemy_id = world.add_enemy(EnemyType::from("base"), String::from("X"), 10, 10);
+            world.set_shield(enemy_id, 3);
+            world.set_health_change(enemy_id, HealthChange::damage(5));
+        }
+
+        resolve_health_change(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.shield.read().unwrap()[0], Some(0));
+        assert_eq!(read_world.health.read().unwrap()[0], Some(8));
+    }
+
+    #[test]
+    fn test_resolve_damage_with_temporary_shield_first() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Hit an enemy with both kinds of shields
+            let enemy_id = world.add_enemy(EnemyType::from("base"), String::from("X"), 10, 10);
+            world.set_shield(enemy_id, 3);
+            world.set_temporary_shield(enemy_id, TemporaryShield { amount: 2, duration: 1000 });
+            world.set_health_change(enemy_id, HealthChange::damage(4));
+        }
+
+        resolve_health_change(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.temporary_shield.read().unwrap()[0], Some(TemporaryShield { amount: 0, duration: 1000 }));
+        assert_eq!(read_world.shield.read().unwrap()[0], Some(1));
+        assert_eq!(read_world.health.read().unwrap()[0], Some(10));
+    }
+}
@@ -0,0 +1,129 @@
+//!
+//! Systems dealing with the state of the game
+//! 
+
+use std::sync::{Arc, RwLock};
+
+use nate_engine::system;
+
+use crate::{GamePhase, TowerDefenseWorld, STARTING_BASE_HEALTH};
+
+/// End the game once the base has been destroyed
+#[system(
+    world=TowerDefenseWorld,
+    _read=[base_health],
+    _write=[game_phase],
+)]
+pub fn check_game_over() {
+    if *base_health == 0 {
+        *game_phase = GamePhase::GameOver;
+    }
+}
+
+/// If a restart has been requested, clear the world and start a new game
+pub fn restart_game(world: Arc<RwLock<TowerDefenseWorld>>) {
+    if *world.read().unwrap().restarting_game.read().unwrap() == Some(true) {
+        let mut write_world = world.write().unwrap();
+        write_world.reset(STARTING_BASE_HEALTH);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::EnemyType;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_check_game_over_base_alive() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(1);
+        }
+
+        check_game_over(world.clone());
+
+        // Make sure the game is still being played
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.game_phase.read().unwrap(), Some(GamePhase::Playing));
+    }
+
+    #[test]
+    fn test_check_game_over_base_destroyed() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components with a destroyed base
+            world.initialize_singular_components(0);
+        }
+
+        check_game_over(world.clone());
+
+        // Make sure the game is over
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.game_phase.read().unwrap(), Some(GamePhase::GameOver));
+    }
+
+    #[test]
+    fn test_restart_game_not_restarting() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Start a game and destroy the base
+            world.new_game(100);
+            world.set_base_health(0);
+            world.set_game_phase(GamePhase::GameOver);
+        }
+
+        restart_game(world.clone());
+
+        // Make sure the game is still over
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.game_phase.read().unwrap(), Some(GamePhase::GameOver));
+        assert_eq!(*read_world.base_health.read().unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_restart_game_resets_world() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Start a game, destroy the base, and request a restart
+            world.new_game(100);
+            world.set_rng(Rng::new(7));
+            world.rng.write().unwrap().as_mut().unwrap().next_u64();
+            world.add_catalog_enemies(EnemyType::from("base"), vec![50, 60, 70]);
+            world.set_base_health(0);
+            world.set_points(500);
+            world.set_level(12);
+            world.set_enemies_killed(40);
+            world.set_game_phase(GamePhase::GameOver);
+            world.set_restarting_game(true);
+        }
+
+        restart_game(world.clone());
+
+        // Make sure the world is back at the start of a new game
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.game_phase.read().unwrap(), Some(GamePhase::Playing));
+        assert_eq!(*read_world.base_health.read().unwrap(), Some(STARTING_BASE_HEALTH));
+        assert_eq!(*read_world.points.read().unwrap(), Some(10));
+        assert_eq!(*read_world.level.read().unwrap(), Some(1));
+        assert_eq!(*read_world.enemies_killed.read().unwrap(), Some(0));
+        assert_eq!(*read_world.restarting_game.read().unwrap(), Some(false));
+        assert_eq!(*read_world.rng.read().unwrap(), Some(Rng::new(7)));
+        assert_eq!(read_world.tower_type.read().unwrap().iter().filter(|v| v.is_some()).count(), 1);
+        assert_eq!(read_world.enemy_type.read().unwrap().iter().filter(|v| v.is_some()).count(), 10);
+    }
+}
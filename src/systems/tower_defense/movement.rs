@@ -4,96 +4,33 @@
 
 use nate_engine::system;
 
-use crate::EnemyType;
+use crate::{GamePhase, StatusEffect, TICK_MILLISECONDS};
 use crate::world::tower_defense_world::TowerDefenseWorld;
 
+/// Move every enemy along the map path towards the base at its speed (slowed by its
+/// status effects) and damage the base with the enemy catalog damage once an enemy reaches it
 #[system(
     world=TowerDefenseWorld,
-    read=[enemy_type],
-    write=[health, enemy_position],
+    read=[enemy_type, speed, status_effects],
+    write=[health, enemy_position, move_progress, enemy_tile],
     _write=[base_health],
-    filter=[*enemy_type == EnemyType::Base],
+    _read=[enemy_catalog, game_phase, map],
+    filter=[*game_phase == GamePhase::Playing, *health > 0],
 )]
-pub fn base_enemy_movement_system() {
-    *enemy_position = enemy_position.saturating_sub(1);
-    if *enemy_position == 0 {
-        *base_health = base_health.saturating_sub(1);
-        *health = 0;
-    }
-}
-
-#[system(
-    world=TowerDefenseWorld,
-    read=[enemy_type],
-    write=[health, enemy_position],
-    _write=[base_health],
-    filter=[*enemy_type == EnemyType::Second]
-)]
-pub fn second_enemy_movement_system() {
-    *enemy_position = enemy_position.saturating_sub(2);
-    if *enemy_position == 0 {
-        *base_health = base_health.saturating_sub(2);
-        *health = 0;
-    }
-}
-
-#[system(
-    world=TowerDefenseWorld,
-    read=[enemy_type],
-    write=[health, enemy_position],
-    _write=[base_health],
-    filter=[*enemy_type == EnemyType::Third]
-)]
-pub fn third_enemy_movement_system() {
-    *enemy_position = enemy_position.saturating_sub(2);
-    if *enemy_position == 0 {
-        *base_health = base_health.saturating_sub(4);
-        *health = 0;
-    }
-}
-
-#[system(
-    world=TowerDefenseWorld,
-    read=[enemy_type],
-    write=[health, enemy_position],
-    _write=[base_health],
-    filter=[*enemy_type == EnemyType::Fourth]
-)]
-pub fn fourth_enemy_movement_system() {
-    *enemy_position = enemy_position.saturating_sub(3);
-    if *enemy_position == 0 {
-        *base_health = base_health.saturating_sub(4);
-        *health = 0;
-    }
-}
-
-#[system(
-    world=TowerDefenseWorld,
-    read=[enemy_type],
-    write=[health, enemy_position],
-    _write=[base_health],
-    filter=[*enemy_type == EnemyType::Fifth]
-)]
-pub fn fifth_enemy_movement_system() {
-    *enemy_position = enemy_position.saturating_sub(3);
-    if *enemy_position == 0 {
-        *base_health = base_health.saturating_sub(6);
-        *health = 0;
-    }
-}
-
-#[system(
-    world=TowerDefenseWorld,
-    read=[enemy_type],
-    write=[health, enemy_position],
-    _write=[base_health],
-    filter=[*enemy_type == EnemyType::Final]
-)]
-pub fn final_enemy_movement_system() {
-    *enemy_position = enemy_position.saturating_sub(1);
-    if *enemy_position == 0 {
-        *base_health = base_health.saturating_sub(20);
-        *health = 0;
+pub fn enemy_movement_system() {
+    // The slowest status effect decides how fast the enemy moves
+    let speed_percent = StatusEffect::combined_speed_percent(status_effects);
+    *move_progress += speed * TICK_MILLISECONDS * speed_percent / 100;
+    let steps = *move_progress / 1000;
+    *move_progress %= 1000;
+
+    if steps > 0 {
+        *enemy_position = enemy_position.saturating_sub(steps);
+        *enemy_tile = map.path_coordinates(*enemy_position);
+        if *enemy_position == 0 {
+            *base_health = base_health.saturating_sub(enemy_catalog.get(enemy_type).damage);
+            *health = 0;
+        }
     }
 }
 
@@ -101,8 +38,14 @@ pub fn final_enemy_movement_system() {
 pub mod tests {
     use super::*;
 
+    use crate::{EnemyType, StatusEffect};
+    use crate::world::map::Map;
+
+    /// The number of movement updates in one second
+    const TICKS_PER_SECOND: u32 = 1000 / TICK_MILLISECONDS;
+
     #[test]
-    fn test_base_enemy_moves_one_space_per_update() {
+    fn test_base_enemy_moves_one_space_per_second() {
         let world = TowerDefenseWorld::new();
 
         let enemy_id: usize;
@@ -114,14 +57,20 @@ pub mod tests {
 
             // Add an enemy at position 30
             enemy_id = world.add_enemy(
-                EnemyType::Base,
+                EnemyType::from("base"),
                 String::from("X"),
                 30,
                 1
             );
         }
 
-        base_enemy_movement_system(world.clone());
+        // Check that the enemy hasn't moved before a second has passed
+        for _ in 0..(TICKS_PER_SECOND - 1) {
+            enemy_movement_system(world.clone());
+        }
+        assert_eq!(world.read().unwrap().enemy_position.read().unwrap()[enemy_id].unwrap(), 30);
+
+        enemy_movement_system(world.clone());
 
         // Check that the Enemy moved to position 29
         let read_world = world.read().unwrap();
@@ -141,14 +90,16 @@ pub mod tests {
 
             // Add an enemy at position 1
             enemy_id = world.add_enemy(
-                EnemyType::Base,
+                EnemyType::from("base"),
                 String::from("X"),
                 1,
                 1
             );
         }
 
-        base_enemy_movement_system(world.clone());
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
 
         // Check that the enemy is at position 0, has 0 health, and the bas health is 99
         let read_world = world.read().unwrap();
@@ -158,7 +109,7 @@ pub mod tests {
     }
 
     #[test]
-    fn test_second_enemy_moves_two_spaces_per_update() {
+    fn test_second_enemy_moves_two_spaces_per_second() {
         let world = TowerDefenseWorld::new();
 
         let enemy_id: usize;
@@ -168,10 +119,12 @@ pub mod tests {
             // Initialize the singular components
             world.initialize_singular_components(100);
 
-            enemy_id = world.add_second_enemy(30);
+            enemy_id = world.add_catalog_enemy(EnemyType::from("second"), 30);
         }
 
-        second_enemy_movement_system(world.clone());
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
 
         // Check that the Enemy moved to position 28
         let read_world = world.read().unwrap();
@@ -190,10 +143,12 @@ pub mod tests {
             world.initialize_singular_components(100);
 
             // Add an enemy at position 1
-            enemy_id = world.add_second_enemy(1);
+            enemy_id = world.add_catalog_enemy(EnemyType::from("second"), 1);
         }
 
-        second_enemy_movement_system(world.clone());
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
 
         // Check that the enemy is at position 0, has 0 health, and the base health is 98
         let read_world = world.read().unwrap();
@@ -201,4 +156,181 @@ pub mod tests {
         assert_eq!(read_world.health.read().unwrap()[enemy_id].unwrap(), 0);
         assert_eq!(read_world.base_health.read().unwrap().unwrap(), 98);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_enemy_moves_at_speed_component() {
+        let world = TowerDefenseWorld::new();
+
+        let enemy_id: usize;
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a base enemy that has been sped up
+            enemy_id = world.add_catalog_enemy(EnemyType::from("base"), 30);
+            world.set_speed(enemy_id, 5);
+        }
+
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
+
+        // Check that the enemy moved 5 positions
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_position.read().unwrap()[enemy_id].unwrap(), 25);
+    }
+
+    #[test]
+    fn test_dead_enemies_do_not_damage_base() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a dead final enemy next to the base
+            world.add_enemy(EnemyType::from("final"), String::from("M"), 1, 0);
+        }
+
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
+
+        // Check that the base wasn't damaged
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.base_health.read().unwrap().unwrap(), 100);
+    }
+
+    #[test]
+    fn test_enemy_follows_map_path() {
+        let world = TowerDefenseWorld::new();
+
+        let enemy_id: usize;
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components with a path that turns a corner
+            world.initialize_singular_components(100);
+            world.set_map(Map::parse("S=.\n.=B").unwrap());
+
+            // Add a second enemy on the spawn
+            enemy_id = world.add_catalog_enemy(EnemyType::from("second"), 3);
+        }
+
+        assert_eq!(world.read().unwrap().enemy_tile.read().unwrap()[enemy_id], Some((0, 0)));
+
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
+
+        // Check that the enemy walked around the corner
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_position.read().unwrap()[enemy_id], Some(1));
+        assert_eq!(read_world.enemy_tile.read().unwrap()[enemy_id], Some((1, 1)));
+    }
+
+    #[test]
+    fn test_enemies_do_not_move_after_game_over() {
+        let world = TowerDefenseWorld::new();
+
+        let enemy_id: usize;
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components and end the game
+            world.initialize_singular_components(0);
+            world.set_game_phase(GamePhase::GameOver);
+
+            enemy_id = world.add_catalog_enemy(EnemyType::from("base"), 30);
+        }
+
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
+
+        // Check that the enemy did not move
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_position.read().unwrap()[enemy_id].unwrap(), 30);
+    }
+
+    #[test]
+    fn test_enemies_do_not_move_while_paused() {
+        let world = TowerDefenseWorld::new();
+
+        let enemy_id: usize;
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components and pause the game
+            world.initialize_singular_components(100);
+            world.set_game_phase(GamePhase::Paused);
+
+            enemy_id = world.add_catalog_enemy(EnemyType::from("second"), 30);
+        }
+
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
+
+        // Check that the enemy did not move
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_position.read().unwrap()[enemy_id].unwrap(), 30);
+    }
+
+    #[test]
+    fn test_slowed_enemy_moves_at_reduced_speed() {
+        let world = TowerDefenseWorld::new();
+
+        let enemy_id: usize;
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a second enemy (2 positions per second) slowed by half
+            enemy_id = world.add_catalog_enemy(EnemyType::from("second"), 30);
+            world.set_status_effects(enemy_id, vec![StatusEffect::Slow { percent: 50, duration: 10_000 }]);
+        }
+
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
+
+        // Check that the Enemy only moved to position 29
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_position.read().unwrap()[enemy_id].unwrap(), 29);
+    }
+
+    #[test]
+    fn test_frozen_enemy_does_not_move() {
+        let world = TowerDefenseWorld::new();
+
+        let enemy_id: usize;
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a slowed and frozen enemy
+            enemy_id = world.add_catalog_enemy(EnemyType::from("second"), 30);
+            world.set_status_effects(enemy_id, vec![
+                StatusEffect::Slow { percent: 50, duration: 10_000 },
+                StatusEffect::Freeze { duration: 10_000 },
+            ]);
+        }
+
+        for _ in 0..TICKS_PER_SECOND {
+            enemy_movement_system(world.clone());
+        }
+
+        // Check that the Enemy didn't move
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.enemy_position.read().unwrap()[enemy_id].unwrap(), 30);
+    }
+}
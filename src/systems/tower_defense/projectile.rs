@@ -0,0 +1,200 @@
+//!
+//! Projectile Systems
+//! 
+
+use std::sync::{Arc, RwLock};
+
+use crate::{GamePhase, TowerDefenseWorld, TowerType, TICK_MILLISECONDS};
+use crate::systems::tower_defense::tower::hit_enemy;
+
+/// Add the shots fired this tick to the world, fly every shot towards its target, and hit the
+/// targets of the shots that arrive (shots whose target died first miss), queueing the shots
+/// that arrived for removal
+pub fn move_projectiles(world: Arc<RwLock<TowerDefenseWorld>>) {
+    if *world.read().unwrap().game_phase.read().unwrap() != Some(GamePhase::Playing) {
+        return;
+    }
+
+    // Add the shots fired this tick (taking them under the same guard they are added under, so
+    // their targets can't be despawned while they are in neither place)
+    {
+        let mut world = world.write().unwrap();
+        let fired_projectiles = std::mem::take(world.fired_projectiles.write().unwrap().as_mut().unwrap());
+        if !fired_projectiles.is_empty() {
+            world.add_projectiles(fired_projectiles);
+        }
+    }
+
+    // Fly the shots and hit the enemies they reach (under a single world guard, so the enemies
+    // can't be despawned, shifting their ids, before they are hit)
+    let read_world = world.read().unwrap();
+    let tower_catalog_ref = read_world.tower_catalog.read().unwrap();
+    let tower_catalog = tower_catalog_ref.as_ref().unwrap();
+    let mut hits: Vec<(TowerType, usize)> = Vec::new();
+    {
+        let health = read_world.health.read().unwrap();
+        let enemy_tile = read_world.enemy_tile.read().unwrap();
+        let mut removal_entities_ref = read_world.removal_entities.write().unwrap();
+        let removal_entities = removal_entities_ref.as_mut().unwrap();
+        let mut projectile = read_world.projectile.write().unwrap();
+        for (projectile_id, projectile) in projectile.iter_mut().enumerate() {
+            let Some(projectile) = projectile.as_mut() else {
+                continue;
+            };
+            // Shots that already arrived are waiting to be removed
+            if removal_entities.contains(&projectile_id) {
+                continue;
+            }
+
+            // Follow the target while it is alive (flying on to where it was last seen once it has died)
+            projectile.target = projectile.target.filter(|v| health[*v].is_some_and(|v| v > 0));
+            if let Some(tile) = projectile.target.and_then(|v| enemy_tile[v]) {
+                projectile.target_tile = tile;
+            }
+
+            projectile.travelled += tower_catalog.get(&projectile.tower_type).projectile_speed * TICK_MILLISECONDS;
+            if projectile.travelled >= projectile.distance() {
+                if let Some(target) = projectile.target {
+                    hits.push((projectile.tower_type.clone(), target));
+                }
+                removal_entities.push(projectile_id);
+            }
+        }
+    }
+
+    // Hit the enemies (the damage is applied once health changes are next resolved)
+    let path_length = read_world.map.read().unwrap().as_ref().unwrap().path_length();
+    for (tower_type, target) in hits {
+        hit_enemy(&read_world, tower_catalog.get(&tower_type), target, path_length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{EnemyType, HealthChange, Projectile, TowerTarget};
+    use crate::systems::tower_defense::{alive_enemies::remove_dead_entities, tower::tower_attack_ai};
+    use crate::world::map::Map;
+
+    #[test]
+    fn test_cannon_shot_hits_after_travel_time() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a cannon (4 tiles per second) 2 tiles from a frozen final enemy
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("cannon"), TowerTarget::First, (20, 2));
+            let _ = world.add_catalog_enemy(EnemyType::from("final"), 20);
+        }
+
+        tower_attack_ai(world.clone());
+        move_projectiles(world.clone());
+        assert!(world.read().unwrap().projectile.read().unwrap()[2].is_some());
+        assert_eq!(world.read().unwrap().health_change.read().unwrap()[1], Some(HealthChange::default()));
+
+        // The shot takes half a second to fly 2 tiles
+        for _ in 1..(500 / TICK_MILLISECONDS) {
+            move_projectiles(world.clone());
+        }
+
+        // Make sure the shot hit (15 damage less 2 armor) and was queued for removal
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::damage(13)));
+        assert_eq!(*read_world.removal_entities.read().unwrap(), Some(vec![2]));
+    }
+
+    #[test]
+    fn test_fired_shot_keeps_target_when_entities_removed_before_adding() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a cannon 2 tiles from a frozen final enemy (after a dead enemy out of range)
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("cannon"), TowerTarget::First, (20, 2));
+            let _ = world.add_enemy(EnemyType::from("base"), String::from("X"), 50, 0);
+            let _ = world.add_catalog_enemy(EnemyType::from("final"), 20);
+        }
+
+        // Removing the dead enemy between firing and adding the shot shifts the shot's target down
+        tower_attack_ai(world.clone());
+        remove_dead_entities(world.clone());
+        assert_eq!(world.read().unwrap().fired_projectiles.read().unwrap().as_ref().unwrap()[0].target, Some(1));
+        for _ in 0..(500 / TICK_MILLISECONDS) {
+            move_projectiles(world.clone());
+        }
+
+        // Make sure the shot hit the final enemy
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::damage(13)));
+        assert_eq!(*read_world.removal_entities.read().unwrap(), Some(vec![2]));
+    }
+
+    #[test]
+    fn test_shot_misses_when_target_dies_first() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a shot flying at an enemy that has just died
+            world.set_map(Map::straight(100));
+            let _ = world.add_enemy(EnemyType::from("base"), String::from("X"), 20, 0);
+            let _ = world.add_enemy(EnemyType::from("base"), String::from("X"), 21, 1);
+            let _ = world.add_projectiles(vec![Projectile {
+                tower_type: TowerType::from("cannon"),
+                target: Some(0),
+                origin: (20, 1),
+                target_tile: (20, 0),
+                travelled: 900,
+            }]);
+        }
+
+        // Removing the dead enemy shifts the shot's id down and makes it miss
+        remove_dead_entities(world.clone());
+        move_projectiles(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.projectile.read().unwrap()[1].as_ref().unwrap().target, None);
+        assert_eq!(read_world.health_change.read().unwrap()[0], Some(HealthChange::default()));
+        assert_eq!(*read_world.removal_entities.read().unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn test_removing_entities_keeps_shots_on_target() {
+        let world = TowerDefenseWorld::new();
+        let mut world = world.write().unwrap();
+
+        // Initialize the singular components
+        world.initialize_singular_components(100);
+
+        // Add shots at the first and third of three enemies
+        let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![10, 11, 12]);
+        let _ = world.add_projectiles(vec![0, 2].into_iter().map(|target| Projectile {
+            tower_type: TowerType::from("cannon"),
+            target: Some(target),
+            origin: (0, 0),
+            target_tile: (0, 0),
+            travelled: 0,
+        }).collect());
+
+        world.despawn_entities(vec![1]);
+
+        let projectile = world.projectile.read().unwrap();
+        assert_eq!(projectile[2].as_ref().unwrap().target, Some(0));
+        assert_eq!(projectile[3].as_ref().unwrap().target, Some(1));
+    }
+}
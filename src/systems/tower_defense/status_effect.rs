@@ -0,0 +1,113 @@
+//!
+//! Status Effect Systems
+//! 
+
+use nate_engine::system;
+
+use crate::{GamePhase, TowerDefenseWorld, TICK_MILLISECONDS};
+
+/// Deal the damage of every poison effect on an enemy (ignoring armor and resistance, so
+/// poison hurts armored enemies as much as any other) independently of tower fire rates
+#[system(
+    world=TowerDefenseWorld,
+    read=[status_effects],
+    write=[health],
+    _write=[overkill_damage],
+    _read=[game_phase],
+    filter=[*game_phase == GamePhase::Playing, *health > 0],
+)]
+pub fn poison_damage() {
+    let damage: u32 = status_effects.iter().map(|v| v.poison_damage(TICK_MILLISECONDS)).sum();
+    *overkill_damage += damage.saturating_sub(*health);
+    *health = health.saturating_sub(damage);
+}
+
+/// Count down every enemy's status effects and remove the effects that have worn off
+#[system(
+    world=TowerDefenseWorld,
+    write=[status_effects],
+    _read=[game_phase],
+    filter=[*game_phase == GamePhase::Playing],
+)]
+pub fn expire_status_effects() {
+    *status_effects = status_effects.iter()
+        .filter_map(|v| v.tick(TICK_MILLISECONDS))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{EnemyType, StatusEffect};
+
+    #[test]
+    fn test_status_effects_wear_off() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add an enemy that is slowed for longer than it is frozen
+            let enemy_id = world.add_catalog_enemy(EnemyType::from("base"), 30);
+            world.set_status_effects(enemy_id, vec![
+                StatusEffect::Slow { percent: 50, duration: 2 * TICK_MILLISECONDS },
+                StatusEffect::Freeze { duration: TICK_MILLISECONDS },
+            ]);
+        }
+
+        // The freeze wears off after one tick
+        expire_status_effects(world.clone());
+        assert_eq!(
+            world.read().unwrap().status_effects.read().unwrap()[0],
+            Some(vec![StatusEffect::Slow { percent: 50, duration: TICK_MILLISECONDS }]),
+        );
+
+        // The slow wears off after two ticks
+        expire_status_effects(world.clone());
+        assert_eq!(world.read().unwrap().status_effects.read().unwrap()[0], Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_stacked_poison_deals_damage_every_second() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a final enemy (20 health, 2 armor) poisoned twice
+            let enemy_id = world.add_catalog_enemy(EnemyType::from("final"), 30);
+            world.set_status_effects(enemy_id, vec![
+                StatusEffect::Poison { damage: 1, duration: 2000 },
+                StatusEffect::Poison { damage: 2, duration: 1000 },
+            ]);
+        }
+
+        // Run the poison for one tick less than a second
+        for _ in 0..(1000 / TICK_MILLISECONDS - 1) {
+            poison_damage(world.clone());
+            expire_status_effects(world.clone());
+        }
+        assert_eq!(world.read().unwrap().health.read().unwrap()[0], Some(20));
+
+        // Both stacks deal their damage once a second runs out
+        poison_damage(world.clone());
+        expire_status_effects(world.clone());
+        assert_eq!(world.read().unwrap().health.read().unwrap()[0], Some(17));
+
+        // The longer stack deals its damage again a second later and then wears off
+        for _ in 0..(1000 / TICK_MILLISECONDS) {
+            poison_damage(world.clone());
+            expire_status_effects(world.clone());
+        }
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health.read().unwrap()[0], Some(16));
+        assert_eq!(read_world.status_effects.read().unwrap()[0], Some(Vec::new()));
+    }
+}
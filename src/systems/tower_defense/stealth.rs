@@ -0,0 +1,83 @@
+//!
+//! Stealth Detection Systems
+//! 
+
+use nate_engine::system;
+
+use crate::TowerDefenseWorld;
+use crate::world::map::in_range;
+
+/// Mark every enemy that isn't stealthy, or is within the range of a tower with detection,
+/// as detected so towers can target it
+#[system(
+    world=TowerDefenseWorld,
+    read=[enemy_type, enemy_tile],
+    write=[detected],
+    _read=[enemy_catalog, tower_catalog],
+)]
+pub fn detect_stealth_enemies() {
+    *detected = !enemy_catalog.get(enemy_type).stealth || {
+        let tower_type = world.tower_type.read().unwrap();
+        let tower_tile = world.tower_tile.read().unwrap();
+        tower_type.iter().zip(tower_tile.iter()).any(|v| match v {
+            (Some(tower_type), Some(tower_tile)) => {
+                let definition = tower_catalog.get(tower_type);
+                definition.detection && in_range(*tower_tile, definition.range, *enemy_tile)
+            },
+            _ => false,
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{EnemyType, TowerTarget, TowerType};
+    use crate::world::map::Map;
+
+    #[test]
+    fn test_detect_stealth_enemies_in_detector_range() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a ghost next to a watchtower, a ghost far from it, and a regular enemy far from it
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("watchtower"), TowerTarget::First, (20, 1));
+            let _ = world.add_catalog_enemy(EnemyType::from("ghost"), 20);
+            let _ = world.add_catalog_enemy(EnemyType::from("ghost"), 60);
+            let _ = world.add_catalog_enemy(EnemyType::from("base"), 60);
+        }
+
+        detect_stealth_enemies(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(*read_world.detected.read().unwrap(), vec![None, Some(true), Some(false), Some(true)]);
+    }
+
+    #[test]
+    fn test_regular_towers_dont_detect_stealth_enemies() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a ghost right next to a fourth tower
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("fourth"), TowerTarget::First, (20, 1));
+            let _ = world.add_catalog_enemy(EnemyType::from("ghost"), 20);
+        }
+
+        detect_stealth_enemies(world.clone());
+
+        assert_eq!(world.read().unwrap().detected.read().unwrap()[1], Some(false));
+    }
+}
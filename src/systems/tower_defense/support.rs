@@ -0,0 +1,169 @@
+//!
+//! Support Enemy Systems
+//! 
+
+use std::sync::{Arc, RwLock};
+
+use nate_engine::system;
+
+use crate::{GamePhase, TowerDefenseWorld, TICK_MILLISECONDS};
+use crate::catalog::enemy_catalog::SupportAbility;
+
+/// Count down the support ability of every support enemy on the map and, once it is ready,
+/// heal or shield the other enemies on the map within its radius
+pub fn support_enemies(world: Arc<RwLock<TowerDefenseWorld>>) {
+    if *world.read().unwrap().game_phase.read().unwrap() != Some(GamePhase::Playing) {
+        return;
+    }
+
+    // Heal and shield the enemies near support enemies under the same world guard they are found
+    // under (so they can't be despawned, shifting their ids, in between)
+    let read_world = world.read().unwrap();
+
+    let health = read_world.health.read().unwrap();
+    let enemy_type = read_world.enemy_type.read().unwrap();
+    let enemy_position = read_world.enemy_position.read().unwrap();
+    let enemy_catalog_ref = read_world.enemy_catalog.read().unwrap();
+    let enemy_catalog = enemy_catalog_ref.as_ref().unwrap();
+    let path_length = read_world.map.read().unwrap().as_ref().unwrap().path_length();
+    let on_map = |entity_id: usize| match (health[entity_id], enemy_position[entity_id]) {
+        (Some(health), Some(position)) => (health > 0 && position > 0 && position < path_length).then_some(position),
+        _ => None,
+    };
+
+    let mut health_change = read_world.health_change.write().unwrap();
+    let mut temporary_shield = read_world.temporary_shield.write().unwrap();
+    let mut support_cooldown = read_world.support_cooldown.write().unwrap();
+    for (support_id, cooldown) in support_cooldown.iter_mut().enumerate() {
+        let (Some(cooldown), Some(enemy_type), Some(support_position)) = (
+            cooldown.as_mut(),
+            enemy_type[support_id].as_ref(),
+            on_map(support_id),
+        ) else {
+            continue;
+        };
+        let Some(ability) = enemy_catalog.get(enemy_type).support else {
+            continue;
+        };
+
+        *cooldown = cooldown.saturating_sub(TICK_MILLISECONDS);
+        if *cooldown > 0 {
+            continue;
+        }
+        *cooldown = ability.interval();
+
+        for entity_id in 0..enemy_position.len() {
+            let Some(position) = on_map(entity_id) else {
+                continue;
+            };
+            if entity_id == support_id || position.abs_diff(support_position) > ability.radius() {
+                continue;
+            }
+            match ability {
+                // The healing is applied when health changes are next resolved
+                SupportAbility::Heal { amount, .. } => if let Some(health_change) = health_change[entity_id].as_mut() {
+                    health_change.healing += amount;
+                },
+                SupportAbility::Shield { amount, duration, .. } => if let Some(temporary_shield) = temporary_shield[entity_id].as_mut() {
+                    temporary_shield.refresh(amount, duration);
+                },
+            }
+        }
+    }
+}
+
+/// Count down every enemy's temporary shield and remove the shields that have worn off
+#[system(
+    world=TowerDefenseWorld,
+    write=[temporary_shield],
+    _read=[game_phase],
+    filter=[*game_phase == GamePhase::Playing],
+)]
+pub fn expire_temporary_shields() {
+    *temporary_shield = temporary_shield.tick(TICK_MILLISECONDS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{EnemyType, TemporaryShield};
+
+    #[test]
+    fn test_healer_heals_nearby_enemies() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a healer (radius 3) with an enemy next to it and an enemy out of its reach
+            let _ = world.add_catalog_enemy(EnemyType::from("healer"), 20);
+            let _ = world.add_catalog_enemy(EnemyType::from("third"), 22);
+            let _ = world.add_catalog_enemy(EnemyType::from("third"), 24);
+        }
+
+        support_enemies(world.clone());
+
+        // Make sure only the nearby enemy is healed and the healer waits for its next heal
+        let read_world = world.read().unwrap();
+        let health_change = read_world.health_change.read().unwrap();
+        assert_eq!(health_change[0].unwrap().healing, 0);
+        assert_eq!(health_change[1].unwrap().healing, 2);
+        assert_eq!(health_change[2].unwrap().healing, 0);
+        assert_eq!(read_world.support_cooldown.read().unwrap()[0], Some(2000));
+    }
+
+    #[test]
+    fn test_shield_bearer_shields_nearby_enemies_until_it_wears_off() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a shield-bearer with an enemy behind it
+            let _ = world.add_catalog_enemy(EnemyType::from("shield-bearer"), 20);
+            let _ = world.add_catalog_enemy(EnemyType::from("base"), 21);
+        }
+
+        support_enemies(world.clone());
+        assert_eq!(
+            world.read().unwrap().temporary_shield.read().unwrap()[1],
+            Some(TemporaryShield { amount: 3, duration: 2000 }),
+        );
+
+        // The shield wears off once its duration runs out
+        for _ in 0..(2000 / TICK_MILLISECONDS) {
+            expire_temporary_shields(world.clone());
+        }
+        assert_eq!(world.read().unwrap().temporary_shield.read().unwrap()[1], Some(TemporaryShield::default()));
+    }
+
+    #[test]
+    fn test_support_enemies_off_the_map_do_nothing() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize the singular components
+            world.initialize_singular_components(100);
+
+            // Add a healer that hasn't walked onto the map yet next to a damaged enemy
+            let path_length = world.map.read().unwrap().as_ref().unwrap().path_length();
+            let _ = world.add_catalog_enemy(EnemyType::from("healer"), path_length + 1);
+            let _ = world.add_enemy(EnemyType::from("third"), String::from("T"), path_length - 1, 1);
+        }
+
+        support_enemies(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1].unwrap().healing, 0);
+        assert_eq!(read_world.support_cooldown.read().unwrap()[0], Some(0));
+    }
+}
@@ -2,251 +2,321 @@
 //! Tower-Specific Systems
 //! 
 
-use std::sync::RwLockReadGuard;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 
-use crate::{TowerType, TowerTarget, TowerDefenseWorld};
+use crate::{GamePhase, Layer, Projectile, StatusEffect, TowerType, TowerTarget, TowerDefenseWorld, TICK_MILLISECONDS};
+use crate::catalog::tower_catalog::TowerDefinition;
+use crate::world::map::{in_range, Map};
 
 use nate_engine::system;
 
-/// Find the First, Second, or Last Enemy within range to target.
+/// Find the enemy a tower targets (among the detected enemies on the map within its range in a layer it hits)
 fn find_target_enemy_id(
-    tower_bounds: &(u32, u32),
+    world: &TowerDefenseWorld,
+    tower_tile: &(u32, u32),
+    definition: &TowerDefinition,
     target_enemy: &TowerTarget,
-    enemy_position: RwLockReadGuard<Vec<Option<u32>>>
+    map: &Map,
 ) -> Option<usize> {
-    match target_enemy {
-        TowerTarget::First => {
-            let mut lowest_enemy_id: Option<usize> = None;
-            let mut lowest_enemy_position: u32 = u32::MAX;
-            for (enemy_id, enemy_position) in enemy_position.iter().enumerate().filter(|v| v.1.is_some()) {
-                let enemy_position = enemy_position.unwrap();
-                if tower_bounds.0 == enemy_position {
-                    lowest_enemy_id = Some(enemy_id);
-                    break;
-                } else if tower_bounds.0 < enemy_position &&
-                    enemy_position < tower_bounds.1 &&
-                    enemy_position <= lowest_enemy_position {
-                    lowest_enemy_id = Some(enemy_id);
-                    lowest_enemy_position = enemy_position;
-                }
-            }
-            lowest_enemy_id
-        },
-        TowerTarget::Second => {
-            let mut lowest_enemy_id: Option<usize> = None;
-            let mut lowest_enemy_position: u32 = u32::MAX;
-            let mut second_enemy_id: Option<usize> = None;
-            let mut second_enemy_position: u32 = u32::MAX;
-            for (enemy_id, enemy_position) in enemy_position.iter().enumerate().filter(|v| v.1.is_some()) {
-                let enemy_position = enemy_position.unwrap();
-                if tower_bounds.0 <= enemy_position &&
-                    enemy_position <= tower_bounds.1 &&
-                    enemy_position < second_enemy_position {
-                    if enemy_position < lowest_enemy_position {
-                        second_enemy_id = lowest_enemy_id;
-                        second_enemy_position = lowest_enemy_position;
-                        lowest_enemy_id = Some(enemy_id);
-                        lowest_enemy_position = enemy_position;
-                    } else {
-                        second_enemy_id = Some(enemy_id);
-                        second_enemy_position = enemy_position;
-                    }
-                }
-            }
-
-            if second_enemy_id.is_some() {
-                second_enemy_id
-            } else {
-                lowest_enemy_id
-            }
-        },
-        TowerTarget::Last => {
-            let mut highest_enemy_id: Option<usize> = None;
-            let mut highest_enemy_position: u32 = 0;
-            for (enemy_id, enemy_position) in enemy_position.iter().enumerate().filter(|v| v.1.is_some()) {
-                let enemy_position = enemy_position.unwrap();
-                if tower_bounds.1 == enemy_position {
-                    highest_enemy_id = Some(enemy_id);
-                    break;
-                } else if tower_bounds.0 <= enemy_position &&
-                    enemy_position < tower_bounds.1 &&
-                    enemy_position >= highest_enemy_position {
-                    highest_enemy_id = Some(enemy_id);
-                    highest_enemy_position = enemy_position;
-                }
-            }
-            highest_enemy_id
-        }
-    }
+    let in_range_enemy_ids = find_in_range_enemy_ids(
+        tower_tile,
+        definition.range,
+        &definition.layers,
+        map.path_length(),
+        world.health.read().unwrap(),
+        world.enemy_position.read().unwrap(),
+        world.enemy_tile.read().unwrap(),
+        world.enemy_layer.read().unwrap(),
+        world.detected.read().unwrap(),
+    );
+    choose_target_enemy_id(
+        &in_range_enemy_ids,
+        target_enemy,
+        world.health.read().unwrap(),
+        world.speed.read().unwrap(),
+        world.status_effects.read().unwrap(),
+        world.enemy_tile.read().unwrap(),
+        map.path[map.path.len() - 1],
+    )
 }
 
-/// Do 1 damage per second to a given enemy
-#[system(
-    world=TowerDefenseWorld,
-    read=[tower_type, target_enemy, tower_bounds],
-    filter=[*tower_type == TowerType::Base]
-)]
-pub fn base_tower_attack_ai() {
-    let target_enemy_id = find_target_enemy_id(tower_bounds, target_enemy, world.enemy_position.read().unwrap());
-
-    // Attack the enemy
-    if let Some(target_enemy_id) = target_enemy_id {
-        let mut health = world.health.write().unwrap();
-        if let Some(health_value) = health[target_enemy_id] {
-            health[target_enemy_id] = Some(health_value.saturating_sub(1));
-        }
-    }
+/// Find every detected enemy still alive on the map (enemies killed this tick wait to be removed)
+/// within range in a layer the tower hits, ordered from closest to the base to furthest from the
+/// base along the path
+// every enemy component is read through its own guard
+#[allow(clippy::too_many_arguments)]
+fn find_in_range_enemy_ids(
+    tower_tile: &(u32, u32),
+    range: u32,
+    layers: &[Layer],
+    path_length: u32,
+    health: RwLockReadGuard<Vec<Option<u32>>>,
+    enemy_position: RwLockReadGuard<Vec<Option<u32>>>,
+    enemy_tile: RwLockReadGuard<Vec<Option<(u32, u32)>>>,
+    enemy_layer: RwLockReadGuard<Vec<Option<Layer>>>,
+    detected: RwLockReadGuard<Vec<Option<bool>>>,
+) -> Vec<usize> {
+    let mut in_range_enemies: Vec<(u32, usize)> = enemy_position.iter()
+        .zip(enemy_tile.iter())
+        .zip(enemy_layer.iter())
+        .zip(detected.iter())
+        .enumerate()
+        .filter_map(|(enemy_id, v)| match v {
+            (((Some(position), Some(tile)), Some(layer)), Some(true))
+                if *position < path_length && health[enemy_id] != Some(0) && layers.contains(layer) && in_range(*tower_tile, range, *tile) => {
+                Some((*position, enemy_id))
+            },
+            _ => None,
+        })
+        .collect();
+    in_range_enemies.sort();
+    in_range_enemies.into_iter().map(|(_, enemy_id)| enemy_id).collect()
 }
 
-/// Do 1 damage per 0.5 seconds to a given enemy
-#[system(
-    world=TowerDefenseWorld,
-    read=[tower_type, target_enemy, tower_bounds],
-    filter=[*tower_type == TowerType::Second]
-)]
-pub fn second_tower_attack_ai() {
-    let target_enemy_id = find_target_enemy_id(tower_bounds, target_enemy, world.enemy_position.read().unwrap());
-
-    // Attack the enemy
-    if let Some(target_enemy_id) = target_enemy_id {
-        let mut health = world.health.write().unwrap();
-        if let Some(health_value) = health[target_enemy_id] {
-            health[target_enemy_id] = Some(health_value.saturating_sub(1));
-        }
+/// Choose the enemy to target from the enemies in range (ordered from closest to the base to
+/// furthest from the base along the path, which also breaks ties)
+fn choose_target_enemy_id(
+    in_range_enemy_ids: &[usize],
+    target_enemy: &TowerTarget,
+    health: RwLockReadGuard<Vec<Option<u32>>>,
+    speed: RwLockReadGuard<Vec<Option<u32>>>,
+    status_effects: RwLockReadGuard<Vec<Option<Vec<StatusEffect>>>>,
+    enemy_tile: RwLockReadGuard<Vec<Option<(u32, u32)>>>,
+    base_tile: (u32, u32),
+) -> Option<usize> {
+    let enemy_ids = in_range_enemy_ids.iter().copied();
+    match target_enemy {
+        TowerTarget::First => in_range_enemy_ids.first().copied(),
+        TowerTarget::Second => in_range_enemy_ids.get(1).or(in_range_enemy_ids.first()).copied(),
+        TowerTarget::Last => in_range_enemy_ids.last().copied(),
+        TowerTarget::Strongest => enemy_ids.rev().max_by_key(|v| health[*v]),
+        TowerTarget::Weakest => enemy_ids.min_by_key(|v| health[*v]),
+        // Rank by the speed enemies are actually moving at (slowed or frozen by their status effects)
+        TowerTarget::Fastest => enemy_ids.rev().max_by_key(|v| {
+            speed[*v].unwrap_or(0) * StatusEffect::combined_speed_percent(status_effects[*v].as_deref().unwrap_or_default())
+        }),
+        TowerTarget::Closest => enemy_ids.min_by_key(|v| {
+            let (x, y) = enemy_tile[*v].unwrap_or_default();
+            x.abs_diff(base_tile.0).pow(2) + y.abs_diff(base_tile.1).pow(2)
+        }),
     }
 }
 
-/// Do 3 damage per second to a given enemy
-#[system(
-    world=TowerDefenseWorld,
-    read=[tower_type, target_enemy, tower_bounds],
-    filter=[*tower_type == TowerType::Third]
-)]
-pub fn third_tower_attack_ai() {
-    let target_enemy_id = find_target_enemy_id(tower_bounds, target_enemy, world.enemy_position.read().unwrap());
+/// Find every enemy on the map (in a layer the tower hits) within a blast radius of the targeted enemy
+fn find_splash_enemy_ids(
+    target_enemy_id: usize,
+    splash_radius: u32,
+    layers: &[Layer],
+    path_length: u32,
+    enemy_position: RwLockReadGuard<Vec<Option<u32>>>,
+    enemy_tile: RwLockReadGuard<Vec<Option<(u32, u32)>>>,
+    enemy_layer: RwLockReadGuard<Vec<Option<Layer>>>,
+) -> Vec<usize> {
+    let Some(target_tile) = enemy_tile[target_enemy_id] else {
+        return Vec::new();
+    };
+    enemy_position.iter()
+        .zip(enemy_tile.iter())
+        .zip(enemy_layer.iter())
+        .enumerate()
+        .filter_map(|(enemy_id, v)| match v {
+            ((Some(position), Some(tile)), Some(layer))
+                if *position < path_length && layers.contains(layer) && in_range(target_tile, splash_radius, *tile) => {
+                Some(enemy_id)
+            },
+            _ => None,
+        })
+        .collect()
+}
 
-    // Attack the enemy
-    if let Some(target_enemy_id) = target_enemy_id {
-        let mut health = world.health.write().unwrap();
-        if let Some(health_value) = health[target_enemy_id] {
-            health[target_enemy_id] = Some(health_value.saturating_sub(3));
+/// Hit an enemy and every enemy in the blast with a tower's attack, adding the tower's damage
+/// (multiplied on a critical hit and reduced by each enemy's armor or resistance) to their health
+/// changes and applying the tower's status effect to every enemy that doesn't dodge
+pub fn hit_enemy(world: &TowerDefenseWorld, definition: &TowerDefinition, target_enemy_id: usize, path_length: u32) {
+    let splash_enemy_ids = match definition.splash_radius {
+        0 => vec![target_enemy_id],
+        splash_radius => find_splash_enemy_ids(
+            target_enemy_id,
+            splash_radius,
+            &definition.layers,
+            path_length,
+            world.enemy_position.read().unwrap(),
+            world.enemy_tile.read().unwrap(),
+            world.enemy_layer.read().unwrap(),
+        ),
+    };
+
+    // Roll for a critical hit and for each enemy dodging
+    let (damage, enemy_ids) = {
+        let mut rng_ref = world.rng.write().unwrap();
+        let rng = rng_ref.as_mut().expect("Rng must be initialized");
+        let damage = match rng.chance(definition.crit_chance) {
+            true => definition.damage * definition.crit_multiplier / 100,
+            false => definition.damage,
+        };
+        let enemy_type = world.enemy_type.read().unwrap();
+        let enemy_catalog_ref = world.enemy_catalog.read().unwrap();
+        let enemy_catalog = enemy_catalog_ref.as_ref().expect("Enemy Catalog must be loaded");
+        let enemy_ids: Vec<usize> = splash_enemy_ids.into_iter()
+            .filter(|v| !enemy_type[*v].as_ref().is_some_and(|v| rng.chance(enemy_catalog.get(v).dodge_chance)))
+            .collect();
+        (damage, enemy_ids)
+    };
+
+    // Armor and resistance reduce the damage of every hit
+    {
+        let enemy_type = world.enemy_type.read().unwrap();
+        let enemy_catalog_ref = world.enemy_catalog.read().unwrap();
+        let enemy_catalog = enemy_catalog_ref.as_ref().expect("Enemy Catalog must be loaded");
+        let mut health_change = world.health_change.write().unwrap();
+        for enemy_id in enemy_ids.iter() {
+            if let (Some(health_change), Some(enemy_type)) = (health_change[*enemy_id].as_mut(), enemy_type[*enemy_id].as_ref()) {
+                let damage = enemy_catalog.get(enemy_type).mitigated_damage(definition.damage_type, damage);
+                health_change.damage += damage;
+            }
         }
     }
-}
-
-/// Do 2 damage per 0.5 seconds to a given enemy
-#[system(
-    world=TowerDefenseWorld,
-    read=[tower_type, target_enemy, tower_bounds],
-    filter=[*tower_type == TowerType::Fourth]
-)]
-pub fn fourth_tower_attack_ai() {
-    let target_enemy_id = find_target_enemy_id(tower_bounds, target_enemy, world.enemy_position.read().unwrap());
 
-    // Attack the enemy
-    if let Some(target_enemy_id) = target_enemy_id {
-        let mut health = world.health.write().unwrap();
-        if let Some(health_value) = health[target_enemy_id] {
-            health[target_enemy_id] = Some(health_value.saturating_sub(2));
+    // Apply the tower's status effect (replacing any effect of the same kind that doesn't stack)
+    if let Some(status_effect) = definition.status_effect {
+        let mut status_effects = world.status_effects.write().unwrap();
+        for enemy_id in enemy_ids.iter() {
+            if let Some(status_effects) = status_effects[*enemy_id].as_mut() {
+                if !status_effect.stacks() {
+                    status_effects.retain(|v| !v.same_kind(&status_effect));
+                }
+                status_effects.push(status_effect);
+            }
         }
     }
 }
 
-/// Do 3 damage per 0.5 seconds to a given enemy
+/// Count down each tower's cooldown and, once it has reloaded, attack the targeted enemy (or fire
+/// a shot at it for towers with projectiles) and wait for the fire interval
 #[system(
     world=TowerDefenseWorld,
-    read=[tower_type, target_enemy, tower_bounds],
-    filter=[*tower_type == TowerType::Fifth]
+    read=[tower_type, target_enemy, tower_tile],
+    write=[attack_cooldown],
+    _write=[fired_projectiles],
+    _read=[tower_catalog, game_phase, map],
+    filter=[*game_phase == GamePhase::Playing],
 )]
-pub fn fifth_tower_attack_ai() {
-    let target_enemy_id = find_target_enemy_id(tower_bounds, target_enemy, world.enemy_position.read().unwrap());
-
-    // Attack the enemy
-    if let Some(target_enemy_id) = target_enemy_id {
-        let mut health = world.health.write().unwrap();
-        if let Some(health_value) = health[target_enemy_id] {
-            health[target_enemy_id] = Some(health_value.saturating_sub(3));
+pub fn tower_attack_ai() {
+    *attack_cooldown = attack_cooldown.saturating_sub(TICK_MILLISECONDS);
+
+    let definition = tower_catalog.get(tower_type);
+    if *attack_cooldown == 0 && (definition.damage > 0 || definition.status_effect.is_some()) {
+        let target_enemy_id = find_target_enemy_id(&world, tower_tile, definition, target_enemy, map);
+
+        // Attack the enemy right away or fire a shot at it (the damage is applied once every tower has attacked)
+        if let Some(target_enemy_id) = target_enemy_id {
+            match definition.projectile_speed {
+                0 => hit_enemy(&world, definition, target_enemy_id, map.path_length()),
+                _ => fired_projectiles.push(Projectile {
+                    tower_type: tower_type.clone(),
+                    target: Some(target_enemy_id),
+                    origin: *tower_tile,
+                    target_tile: world.enemy_tile.read().unwrap()[target_enemy_id].unwrap_or(*tower_tile),
+                    travelled: 0,
+                }),
+            }
+            *attack_cooldown = definition.fire_interval;
         }
     }
 }
 
-/// Do 3 damage per 0.25 seconds to a given enemy
-#[system(
-    world=TowerDefenseWorld,
-    read=[tower_type, target_enemy, tower_bounds],
-    filter=[*tower_type == TowerType::Final]
-)]
-pub fn final_tower_attack_ai() {
-    let target_enemy_id = find_target_enemy_id(tower_bounds, target_enemy, world.enemy_position.read().unwrap());
+/// Check whether the tower under the placement cursor should be upgraded (or branched) and,
+/// if so, upgrade the tower (building a new tower if the cursor is on a free buildable tile)
+pub fn upgrade_tower(world: Arc<RwLock<TowerDefenseWorld>>) {
+    // Take the upgrade requests (ignoring requests while the game isn't being played)
+    let branching_tower = {
+        let world = world.read().unwrap();
+        let upgrading_tower = world.upgrading_tower.write().unwrap().replace(false) == Some(true);
+        let branching_tower = world.branching_tower.write().unwrap().replace(None).flatten();
+        if !(upgrading_tower || branching_tower.is_some()) || *world.game_phase.read().unwrap() != Some(GamePhase::Playing) {
+            return;
+        }
+        branching_tower
+    };
+
+    let mut world = world.write().unwrap();
+    let cursor = world.cursor.read().unwrap().expect("Cursor must be initialized");
+    let points = world.points.read().unwrap().expect("Points must be initialized");
+
+    // Find the tower to upgrade (new towers are upgraded from a broken tower)
+    let tower_id = world.tower_at(cursor);
+    let current_tower_type = match tower_id {
+        Some(tower_id) => world.tower_type.read().unwrap()[tower_id].clone().expect("Tower must have a Tower Type"),
+        None if world.can_build_tower(cursor) => TowerType::broken(),
+        None => return,
+    };
+
+    let upgrade_price = {
+        let tower_catalog = world.tower_catalog.read().unwrap();
+        let tower_catalog = tower_catalog.as_ref().expect("Tower Catalog must be loaded");
+        let upgrade = match branching_tower {
+            Some(branch) => tower_catalog.branch(&current_tower_type, branch),
+            None => tower_catalog.upgrade(&current_tower_type),
+        };
+        match upgrade {
+            Some(upgrade) => tower_catalog.get(upgrade).cost,
+            None => return,
+        }
+    };
 
-    // Attack the enemy
-    if let Some(target_enemy_id) = target_enemy_id {
-        let mut health = world.health.write().unwrap();
-        if let Some(health_value) = health[target_enemy_id] {
-            health[target_enemy_id] = Some(health_value.saturating_sub(3));
+    if points >= upgrade_price {
+        let tower_id = tower_id.unwrap_or_else(|| world.add_broken_tower(cursor));
+        match branching_tower {
+            Some(branch) => world.branch_tower(tower_id, &current_tower_type, branch),
+            None => world.upgrade_tower(tower_id, &current_tower_type),
         }
+        world.invest_points(tower_id, upgrade_price);
+        world.set_points(points - upgrade_price);
     }
 }
 
-/// Check whether a tower should be upgraded and, if so, upgrade the tower
+/// Check whether the tower under the placement cursor should be sold and, if so, sell the tower
+/// back into the tower it was upgraded from (refunding part of the points spent on the upgrade)
 #[system(
     world=TowerDefenseWorld,
-    _write=[upgrading_tower, points],
-    _read=[selected_tower],
+    _write=[downgrading_tower, points],
+    _read=[game_phase],
 )]
-pub fn upgrade_tower() {
-    if *upgrading_tower {
-        let mut current_tower_type: Option<TowerType> = None;
-        let mut current_entity_id: Option<usize> = None;
-
-        let mut current_tower = 0;
-        for (entity_id, tower_type) in world.tower_type.read().unwrap().iter().enumerate().filter(|v| v.1.is_some()) {
-            if current_tower == *selected_tower {
-                current_tower_type = Some(tower_type.unwrap());
-                current_entity_id = Some(entity_id);
-                break;
-            }
-            current_tower += 1;
-        }
+pub fn downgrade_tower() {
+    // Ignore sell requests while the game isn't being played
+    if *game_phase != GamePhase::Playing {
+        *downgrading_tower = false;
+    }
 
-        if let (Some(current_tower_type), Some(current_entity_id)) = (current_tower_type, current_entity_id) {
-            if *points >= current_tower_type.upgrade_price() {
-                world.upgrade_tower(*selected_tower, current_entity_id, current_tower_type);
-                *points -= current_tower_type.upgrade_price();
-            }
+    if *downgrading_tower {
+        if let Some(tower_id) = world.selected_tower() {
+            let current_tower_type = world.tower_type.read().unwrap()[tower_id].clone().expect("Tower must have a Tower Type");
+            *points += world.sell_tower(tower_id, &current_tower_type);
         }
 
-        *upgrading_tower = false;
+        *downgrading_tower = false;
     }
 }
 
-/// Check whether a tower should be sold and, if so, sell the tower
+/// Check whether the tower under the placement cursor should be demolished and, if so, turn it
+/// straight back into a broken tower (refunding part of every point invested in it)
 #[system(
     world=TowerDefenseWorld,
-    _write=[downgrading_tower, points],
-    _read=[selected_tower],
+    _write=[demolishing_tower, points],
+    _read=[game_phase],
 )]
-pub fn downgrade_tower() {
-    if *downgrading_tower {
-        let mut current_tower_type: Option<TowerType> = None;
-        let mut current_entity_id: Option<usize> = None;
-
-        let mut current_tower = 0;
-        for (entity_id, tower_type) in world.tower_type.read().unwrap().iter().enumerate().filter(|v| v.1.is_some()) {
-            if current_tower == *selected_tower {
-                current_tower_type = Some(tower_type.unwrap());
-                current_entity_id = Some(entity_id);
-                break;
-            }
-            current_tower += 1;
-        }
+pub fn demolish_tower() {
+    // Ignore demolish requests while the game isn't being played
+    if *game_phase != GamePhase::Playing {
+        *demolishing_tower = false;
+    }
 
-        if let (Some(current_tower_type), Some(current_entity_id)) = (current_tower_type, current_entity_id) {
-            world.sell_tower(*selected_tower, current_entity_id, current_tower_type);
-            *points += current_tower_type.sell_price();
+    if *demolishing_tower {
+        if let Some(tower_id) = world.selected_tower() {
+            *points += world.demolish_tower(tower_id);
         }
 
-        *downgrading_tower = false;
+        *demolishing_tower = false;
     }
 }
 
@@ -254,209 +324,323 @@ pub fn downgrade_tower() {
 mod tests {
     use std::sync::RwLock;
 
+    use crate::{DamageType, EnemyType, HealthChange, StatusEffect};
+    use crate::catalog::{enemy_catalog::EnemyCatalog, tower_catalog::TowerCatalog};
+    use crate::systems::tower_defense::damage::resolve_health_change;
+    use crate::systems::tower_defense::projectile::move_projectiles;
+    use crate::world::map::Map;
+
     use super::*;
 
+    /// Find the enemy a tower at (10, 1) with a range of 5 targets on a straight path
+    /// (only enemies at positions 6 to 14 are in range)
+    fn find_straight_path_target(target_enemy: TowerTarget, enemy_positions: Vec<Option<u32>>) -> Option<usize> {
+        let healths = enemy_positions.iter().map(|v| v.map(|_| 1)).collect();
+        let speeds = enemy_positions.iter().map(|v| v.map(|_| 1)).collect();
+        find_straight_path_target_by_stats(target_enemy, enemy_positions, healths, speeds)
+    }
+
+    /// Find the enemy a tower at (10, 1) with a range of 5 targets on a straight path
+    /// from enemies with some health and speed
+    fn find_straight_path_target_by_stats(
+        target_enemy: TowerTarget,
+        enemy_positions: Vec<Option<u32>>,
+        healths: Vec<Option<u32>>,
+        speeds: Vec<Option<u32>>,
+    ) -> Option<usize> {
+        let map = Map::straight(100);
+        let enemy_tiles = RwLock::new(enemy_positions.iter().map(|v| v.map(|v| map.path_coordinates(v))).collect());
+        let enemy_layers = RwLock::new(enemy_positions.iter().map(|v| v.map(|_| Layer::Ground)).collect());
+        let detected = RwLock::new(enemy_positions.iter().map(|v| v.map(|_| true)).collect());
+        let status_effects = RwLock::new(speeds.iter().map(|v| v.map(|_| Vec::new())).collect());
+        let healths = RwLock::new(healths);
+        let speeds = RwLock::new(speeds);
+        let enemy_positions = RwLock::new(enemy_positions);
+        let in_range_enemy_ids = find_in_range_enemy_ids(
+            &(10, 1),
+            5,
+            &[Layer::Ground],
+            map.path_length(),
+            healths.read().unwrap(),
+            enemy_positions.read().unwrap(),
+            enemy_tiles.read().unwrap(),
+            enemy_layers.read().unwrap(),
+            detected.read().unwrap(),
+        );
+        choose_target_enemy_id(
+            &in_range_enemy_ids,
+            &target_enemy,
+            healths.read().unwrap(),
+            speeds.read().unwrap(),
+            status_effects.read().unwrap(),
+            enemy_tiles.read().unwrap(),
+            map.path[map.path.len() - 1],
+        )
+    }
+
     #[test]
     fn test_find_first_target_enemy_in_range() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::First;
-        let enemy_positions = RwLock::new(vec![
-            None,
-            Some(3),
-            Some(6),
-            Some(22),
-            Some(2),
-            Some(1),
-        ]);
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
-            ),
-            Some(5),
+            find_straight_path_target(TowerTarget::First, vec![
+                None,
+                Some(8),
+                Some(12),
+                Some(30),
+                Some(7),
+                Some(3),
+            ]),
+            Some(4),
         );
     }
 
     #[test]
-    fn test_find_first_target_enemy_on_left_bound() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::First;
-        let enemy_positions = RwLock::new(vec![
+    fn test_find_first_target_enemy_on_edge_of_range() {
+        assert_eq!(
+            find_straight_path_target(TowerTarget::First, vec![
+                Some(9),
+                None,
+                Some(14),
+                Some(6),
+                Some(5),
+            ]),
             Some(3),
+        );
+    }
+
+    #[test]
+    fn test_find_first_target_enemy_out_of_range() {
+        assert_eq!(
+            find_straight_path_target(TowerTarget::First, vec![
+                Some(15),
+                Some(20),
+                None,
+                Some(1),
+                Some(5),
+            ]),
             None,
-            Some(6),
-            Some(22),
-            Some(0),
-            None,
-            Some(1),
-        ]);
+        );
+    }
+
+    #[test]
+    fn test_find_target_enemy_ignores_enemies_off_the_map() {
+        let map = Map::straight(10);
+        let health = RwLock::new(vec![Some(1); 3]);
+        let enemy_positions = RwLock::new(vec![Some(10), Some(25), Some(8)]);
+        let enemy_tiles = RwLock::new(vec![Some((9, 0)), Some((9, 0)), Some((8, 0))]);
+        let enemy_layers = RwLock::new(vec![Some(Layer::Ground); 3]);
+        let detected = RwLock::new(vec![Some(true); 3]);
+
+        // Enemies waiting to enter the map are on the spawn but can't be attacked yet
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
+            find_in_range_enemy_ids(
+                &(9, 1),
+                2,
+                &[Layer::Ground],
+                map.path_length(),
+                health.read().unwrap(),
                 enemy_positions.read().unwrap(),
+                enemy_tiles.read().unwrap(),
+                enemy_layers.read().unwrap(),
+                detected.read().unwrap(),
             ),
-            Some(4),
+            vec![2],
         );
     }
 
     #[test]
-    fn test_find_first_target_enemy_out_of_range() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::First;
-        let enemy_positions = RwLock::new(vec![
-            Some(6),
-            Some(8),
-            None,
-            None,
-            Some(10),
-            Some(11),
-            Some(12),
-            Some(6),
-        ]);
+    fn test_find_target_enemy_ignores_dead_enemies() {
+        // Enemies killed this tick can't be attacked while they wait to be removed
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
+            find_straight_path_target_by_stats(
+                TowerTarget::First,
+                vec![Some(8), Some(9), Some(12)],
+                vec![Some(0), Some(2), Some(0)],
+                vec![Some(1); 3],
             ),
-            None,
+            Some(1),
         );
     }
 
     #[test]
     fn test_find_second_target_enemy_in_range() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::Second;
-        let enemy_positions = RwLock::new(vec![
-            None,
-            Some(1),
-            Some(6),
-            Some(7),
-            Some(2),
-            None,
-            Some(3),
-        ]);
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
-            ),
-            Some(4)
+            find_straight_path_target(TowerTarget::Second, vec![
+                None,
+                Some(7),
+                Some(20),
+                Some(8),
+                Some(9),
+            ]),
+            Some(3),
         );
     }
 
     #[test]
     fn test_find_second_target_enemy_only_one_in_range() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::Second;
-        let enemy_positions = RwLock::new(vec![
-            Some(1),
-            Some(6),
-            None,
-            None,
-            Some(7),
-            Some(9),
-        ]);
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
-            ),
+            find_straight_path_target(TowerTarget::Second, vec![
+                Some(7),
+                Some(20),
+                None,
+                Some(21),
+            ]),
             Some(0),
         );
     }
 
     #[test]
     fn test_find_second_target_enemy_out_of_range() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::Second;
-        let enemy_positions = RwLock::new(vec![
-            None,
-            Some(6),
-            Some(7),
-            None,
-            Some(8),
-            Some(9),
-        ]);
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
-            ),
-            None
-        )
+            find_straight_path_target(TowerTarget::Second, vec![
+                None,
+                Some(15),
+                Some(16),
+                None,
+                Some(2),
+            ]),
+            None,
+        );
     }
 
     #[test]
     fn test_find_final_target_enemy_in_range() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::Last;
-        let enemy_positions = RwLock::new(vec![
-            None,
-            None,
-            Some(4),
-            Some(7),
-            Some(100),
-            Some(101),
-        ]);
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
-            ),
+            find_straight_path_target(TowerTarget::Last, vec![
+                None,
+                Some(7),
+                Some(13),
+                Some(20),
+                Some(100),
+            ]),
             Some(2),
         );
     }
 
     #[test]
-    fn test_find_final_target_enemy_on_right_bound() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::Last;
-        let enemy_positions = RwLock::new(vec![
-            Some(4),
+    fn test_find_final_target_enemy_on_edge_of_range() {
+        assert_eq!(
+            find_straight_path_target(TowerTarget::Last, vec![
+                Some(9),
+                Some(14),
+                Some(15),
+            ]),
             Some(1),
+        );
+    }
+
+    #[test]
+    fn test_find_final_target_enemy_out_of_range() {
+        assert_eq!(
+            find_straight_path_target(TowerTarget::Last, vec![
+                Some(15),
+                Some(17),
+                None,
+                Some(5),
+            ]),
             None,
+        );
+    }
+
+    #[test]
+    fn test_find_strongest_target_enemy_in_range() {
+        // The first of the enemies with the most health is targeted
+        assert_eq!(
+            find_straight_path_target_by_stats(
+                TowerTarget::Strongest,
+                vec![Some(12), Some(8), Some(30), Some(10)],
+                vec![Some(5), Some(2), Some(9), Some(5)],
+                vec![Some(1); 4],
+            ),
             Some(3),
-            Some(8),
-            None,
-            Some(6),
-            Some(5),
-        ]);
+        );
+    }
+
+    #[test]
+    fn test_find_weakest_target_enemy_in_range() {
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
+            find_straight_path_target_by_stats(
+                TowerTarget::Weakest,
+                vec![Some(12), Some(8), Some(30), Some(10)],
+                vec![Some(5), Some(2), Some(1), Some(5)],
+                vec![Some(1); 4],
             ),
-            Some(7),
+            Some(1),
         );
     }
 
     #[test]
-    fn test_find_final_target_enemy_out_of_range() {
-        let tower_bounds = (0, 5);
-        let target_enemy = TowerTarget::Last;
-        let enemy_positions = RwLock::new(vec![
-            Some(6),
-            Some(7),
-            None,
-            None,
-            Some(8),
-            Some(9),
+    fn test_find_fastest_target_enemy_in_range() {
+        assert_eq!(
+            find_straight_path_target_by_stats(
+                TowerTarget::Fastest,
+                vec![Some(12), Some(8), Some(10), None],
+                vec![Some(1); 4],
+                vec![Some(3), Some(1), Some(3), None],
+            ),
+            Some(2),
+        );
+    }
+
+    #[test]
+    fn test_find_fastest_target_enemy_by_effective_speed() {
+        let health = RwLock::new(vec![Some(1); 3]);
+        let speed = RwLock::new(vec![Some(3), Some(4), Some(2)]);
+        // The fastest enemy is frozen in place
+        let status_effects = RwLock::new(vec![
+            Some(Vec::new()),
+            Some(vec![StatusEffect::Freeze { duration: 1000 }]),
+            Some(Vec::new()),
         ]);
+        let enemy_tiles = RwLock::new(vec![Some((1, 1)); 3]);
+
         assert_eq!(
-            find_target_enemy_id(
-                &tower_bounds,
-                &target_enemy,
-                enemy_positions.read().unwrap(),
+            choose_target_enemy_id(
+                &[0, 1, 2],
+                &TowerTarget::Fastest,
+                health.read().unwrap(),
+                speed.read().unwrap(),
+                status_effects.read().unwrap(),
+                enemy_tiles.read().unwrap(),
+                (0, 0),
             ),
-            None,
+            Some(0),
+        );
+
+        // Once slowed, a faster enemy moves slower than a full speed enemy
+        status_effects.write().unwrap()[0] = Some(vec![StatusEffect::Slow { percent: 50, duration: 1000 }]);
+        assert_eq!(
+            choose_target_enemy_id(
+                &[0, 1, 2],
+                &TowerTarget::Fastest,
+                health.read().unwrap(),
+                speed.read().unwrap(),
+                status_effects.read().unwrap(),
+                enemy_tiles.read().unwrap(),
+                (0, 0),
+            ),
+            Some(2),
+        );
+    }
+
+    #[test]
+    fn test_find_closest_target_enemy_to_base() {
+        let health = RwLock::new(vec![Some(1); 2]);
+        let speed = RwLock::new(vec![Some(1); 2]);
+        let status_effects = RwLock::new(vec![Some(Vec::new()); 2]);
+        // The enemy further along the path loops away from the base
+        let enemy_tiles = RwLock::new(vec![Some((5, 5)), Some((1, 1))]);
+
+        assert_eq!(
+            choose_target_enemy_id(
+                &[0, 1],
+                &TowerTarget::Closest,
+                health.read().unwrap(),
+                speed.read().unwrap(),
+                status_effects.read().unwrap(),
+                enemy_tiles.read().unwrap(),
+                (0, 0),
+            ),
+            Some(1),
         );
     }
 
@@ -470,18 +654,19 @@ mod tests {
             // Initialize Singular Components
             world.initialize_singular_components(100);
 
-            // Add a base tower
-            let _ = world.add_tower(TowerType::Base, TowerTarget::First, (10, 30), String::from("!"));
+            // Add a base tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_tower(TowerType::from("base"), TowerTarget::First, (20, 1), String::from("!"));
 
-            // Add a few base enemies
-            let _ = world.add_base_enemies(vec![12, 13, 20, 25, 30, 33, 36, 38]);
+            // Add a few base enemies (the first 3 are in range)
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![19, 20, 21, 25, 30]);
         }
 
-        base_tower_attack_ai(world.clone());
+        tower_attack_ai(world.clone());
 
-        // Make sure the first enemy lost health (i.e. it has 0 health now)
+        // Make sure the first enemy was damaged
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.health.read().unwrap()[1].unwrap(), 0);
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::damage(1)));
     }
 
     #[test]
@@ -494,18 +679,19 @@ mod tests {
             // Initialize Singular Components
             world.initialize_singular_components(100);
 
-            // Add a base tower
-            let _ = world.add_tower(TowerType::Base, TowerTarget::Second, (10, 30), String::from("!"));
+            // Add a base tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_tower(TowerType::from("base"), TowerTarget::Second, (20, 1), String::from("!"));
 
-            // Add a few base enemies
-            let _ = world.add_base_enemies(vec![12, 13, 20, 25, 30, 33, 36, 38]);
+            // Add a few base enemies (the first 3 are in range)
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![19, 20, 21, 25, 30]);
         }
 
-        base_tower_attack_ai(world.clone());
+        tower_attack_ai(world.clone());
 
-        // Make sure the second enemy lost health (i.e. it has 0 health now)
+        // Make sure the second enemy was damaged
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.health.read().unwrap()[2].unwrap(), 0);
+        assert_eq!(read_world.health_change.read().unwrap()[2], Some(HealthChange::damage(1)));
     }
 
     #[test]
@@ -518,18 +704,19 @@ mod tests {
             // Initialize Singular Components
             world.initialize_singular_components(100);
 
-            // Add a base tower
-            let _ = world.add_tower(TowerType::Base, TowerTarget::Last, (10, 30), String::from("!"));
+            // Add a base tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_tower(TowerType::from("base"), TowerTarget::Last, (20, 1), String::from("!"));
 
-            // Add a few base enemies
-            let _ = world.add_base_enemies(vec![12, 13, 20, 25, 30, 33, 36, 38]);
+            // Add a few base enemies (the first 3 are in range)
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![19, 20, 21, 25, 30]);
         }
 
-        base_tower_attack_ai(world.clone());
+        tower_attack_ai(world.clone());
 
-        // Make sure the 5th enemy lost health (i.e. it has 0 health now)
+        // Make sure the 3rd enemy was damaged
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.health.read().unwrap()[5].unwrap(), 0);
+        assert_eq!(read_world.health_change.read().unwrap()[3], Some(HealthChange::damage(1)));
     }
 
     #[test]
@@ -542,18 +729,19 @@ mod tests {
             // Initialize Singular Components
             world.initialize_singular_components(100);
 
-            // Add a base tower
-            let _ = world.add_second_tower(TowerTarget::First, 0);
+            // Add a second tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("second"), TowerTarget::First, (6, 1));
 
-            // Add a base enemy
-            let _ = world.add_base_enemies(vec![3]);
+            // Add a base enemy within 4 tiles of the tower
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![3]);
         }
 
-        second_tower_attack_ai(world.clone());
+        tower_attack_ai(world.clone());
 
-        // Make sure the enemy lost health
+        // Make sure the enemy was damaged
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.health.read().unwrap()[1], Some(0));
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::damage(1)));
     }
 
     #[test]
@@ -565,19 +753,19 @@ mod tests {
 
             // Initialize Singular Components
             world.initialize_singular_components(100);
-            world.set_selected_tower(0);
+            world.set_cursor((0, 1));
             world.set_upgrading_tower(false);
             world.set_points(10);
 
             // Add a broken tower
-            let _ = world.add_broken_tower();
+            let _ = world.add_broken_tower((0, 1));
         }
 
         upgrade_tower(world.clone());
 
         // Make sure the broken tower wasn't upgraded and the current points didn't change
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::Broken));
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::broken()));
         assert_eq!(*read_world.points.read().unwrap(), Some(10));
     }
 
@@ -590,20 +778,20 @@ mod tests {
 
             // Initialize Singular Components
             world.initialize_singular_components(100);
-            world.set_selected_tower(0);
+            world.set_cursor((0, 1));
             world.set_upgrading_tower(true);
             world.set_points(10);
 
             // Add a broken tower
-            let _ = world.add_broken_tower();
+            let _ = world.add_broken_tower((0, 1));
         }
 
         upgrade_tower(world.clone());
 
         // Make sure the broken tower was upgraded and the current points is 0
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::Base));
-        assert_eq!(read_world.tower_bounds.read().unwrap()[0], Some((3, 7)));
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("base")));
+        assert_eq!(read_world.tower_tile.read().unwrap()[0], Some((0, 1)));
         assert_eq!(read_world.sprite.read().unwrap()[0], Some(String::from("!")));
         assert_eq!(*read_world.points.read().unwrap(), Some(0));
         assert_eq!(*read_world.upgrading_tower.read().unwrap(), Some(false));
@@ -618,20 +806,20 @@ mod tests {
 
             // Initialize Singular Components
             world.initialize_singular_components(100);
-            world.set_selected_tower(3);
+            world.set_cursor((3, 1));
             world.set_upgrading_tower(true);
             world.set_points(10);
 
             // Add a few broken towers
-            let _ = world.add_broken_towers(10);
+            let _ = world.add_broken_towers((0..10).map(|x| (x, 1)).collect());
         }
 
         upgrade_tower(world.clone());
 
         // Make sure the 4th broken tower was upgraded
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.tower_type.read().unwrap()[3], Some(TowerType::Base));
-        assert_eq!(read_world.tower_bounds.read().unwrap()[3], Some((33, 37)));
+        assert_eq!(read_world.tower_type.read().unwrap()[3], Some(TowerType::from("base")));
+        assert_eq!(read_world.tower_tile.read().unwrap()[3], Some((3, 1)));
         assert_eq!(read_world.sprite.read().unwrap()[3], Some(String::from("!")));
         assert_eq!(*read_world.points.read().unwrap(), Some(0));
         assert_eq!(*read_world.upgrading_tower.read().unwrap(), Some(false));
@@ -646,101 +834,729 @@ mod tests {
 
             // Initialize Singular Components
             world.initialize_singular_components(100);
-            world.set_selected_tower(0);
+            world.set_cursor((0, 1));
             world.set_upgrading_tower(true);
             world.set_points(5);
 
             // Add a broken tower
-            world.add_broken_tower();
+            world.add_broken_tower((0, 1));
         }
 
         upgrade_tower(world.clone());
 
         // Make sure the tower wasn't upgraded
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::Broken));
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::broken()));
         assert_eq!(*read_world.points.read().unwrap(), Some(5));
         assert_eq!(*read_world.upgrading_tower.read().unwrap(), Some(false));
     }
 
     #[test]
-    fn test_downgrade_tower_not_downgrading_tower() {
+    fn test_build_tower_on_free_buildable_tile() {
         let world = TowerDefenseWorld::new();
 
         {
             let mut world = world.write().unwrap();
 
-            // Initialize Singular Components
+            // Initialize Singular Components with the cursor below a straight path
             world.initialize_singular_components(100);
-            world.set_points(10);
+            world.set_map(Map::straight(100));
+            world.set_cursor((40, 1));
+            world.set_upgrading_tower(true);
+            world.set_points(15);
+        }
 
-            // Add a base tower
-            world.add_base_tower(TowerTarget::First, 0);
+        upgrade_tower(world.clone());
+
+        // Make sure a base tower was built under the cursor
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("base")));
+        assert_eq!(read_world.tower_tile.read().unwrap()[0], Some((40, 1)));
+        assert_eq!(read_world.target_enemy.read().unwrap()[0], Some(TowerTarget::First));
+        assert_eq!(*read_world.points.read().unwrap(), Some(5));
+    }
+
+    #[test]
+    fn test_build_splash_tower_on_free_buildable_tile() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components with the cursor below a straight path
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            world.set_cursor((40, 1));
+            world.set_branching_tower(Some(0));
+            world.set_points(20);
         }
 
-        downgrade_tower(world.clone());
+        upgrade_tower(world.clone());
 
-        // Make sure the tower wasn't sold
+        // Make sure a splash tower was built under the cursor
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::Base));
-        assert_eq!(*read_world.points.read().unwrap(), Some(10));
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("splash")));
+        assert_eq!(read_world.sprite.read().unwrap()[0], Some(String::from("*")));
+        assert_eq!(*read_world.points.read().unwrap(), Some(5));
+        assert_eq!(*read_world.branching_tower.read().unwrap(), Some(None));
     }
 
     #[test]
-    fn test_sell_first_tower() {
+    fn test_branch_tower_without_branch() {
         let world = TowerDefenseWorld::new();
 
         {
             let mut world = world.write().unwrap();
 
-            // Initialize Singular Components
+            // Initialize Singular Components with the cursor on a third tower
             world.initialize_singular_components(100);
-            world.set_points(10);
-            world.set_selected_tower(0);
-            world.set_downgrading_tower(true);
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("third"), TowerTarget::First, (40, 1));
+            world.set_cursor((40, 1));
+            world.set_branching_tower(Some(0));
+            world.set_points(100);
+        }
 
-            // Add a base tower
-            world.add_base_tower(TowerTarget::First, 0);
+        upgrade_tower(world.clone());
+
+        // Make sure the tower and points didn't change
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("third")));
+        assert_eq!(*read_world.points.read().unwrap(), Some(100));
+    }
+
+    #[test]
+    fn test_branch_tower_into_later_branch() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components with the cursor on a second tower
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("second"), TowerTarget::First, (40, 1));
+            world.set_cursor((40, 1));
+            world.set_branching_tower(Some(2));
+            world.set_points(100);
         }
 
-        downgrade_tower(world.clone());
+        upgrade_tower(world.clone());
 
-        // Make sure the tower was sold
+        // Make sure the tower became a sniper (its third branch)
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::Broken));
-        assert_eq!(read_world.tower_bounds.read().unwrap()[0], Some((0, 0)));
-        assert_eq!(read_world.sprite.read().unwrap()[0], Some(String::from("-")));
-        assert_eq!(*read_world.points.read().unwrap(), Some(15));
-        assert_eq!(*read_world.downgrading_tower.read().unwrap(), Some(false));
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("sniper")));
+        assert_eq!(*read_world.points.read().unwrap(), Some(65));
     }
 
     #[test]
-    fn test_sell_tower() {
+    fn test_sell_splash_tower() {
         let world = TowerDefenseWorld::new();
 
         {
             let mut world = world.write().unwrap();
 
-            // Initialize Singular Components
+            // Initialize Singular Components with the cursor on a splash tower
             world.initialize_singular_components(100);
-            world.set_points(10);
-            world.set_selected_tower(2);
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("splash"), TowerTarget::First, (40, 1));
+            world.set_cursor((40, 1));
             world.set_downgrading_tower(true);
-
-            // Add 3 base towers
-            world.add_base_tower(TowerTarget::First, 0);
-            world.add_base_tower(TowerTarget::First, 1);
-            world.add_base_tower(TowerTarget::First, 2);
         }
 
         downgrade_tower(world.clone());
 
-        // Make sure the tower was sold
+        // Make sure the splash tower was sold back into a broken tower (refunding 75% of its cost)
         let read_world = world.read().unwrap();
-        assert_eq!(read_world.tower_type.read().unwrap()[2], Some(TowerType::Broken));
-        assert_eq!(read_world.tower_bounds.read().unwrap()[2], Some((0, 0)));
-        assert_eq!(read_world.sprite.read().unwrap()[2], Some(String::from("-")));
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::broken()));
+        assert_eq!(*read_world.points.read().unwrap(), Some(21));
+    }
+
+    #[test]
+    fn test_build_tower_on_path() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components with the cursor on a straight path
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            world.set_cursor((40, 0));
+            world.set_upgrading_tower(true);
+            world.set_points(15);
+        }
+
+        upgrade_tower(world.clone());
+
+        // Make sure no tower was built
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap().len(), 0);
         assert_eq!(*read_world.points.read().unwrap(), Some(15));
-        assert_eq!(*read_world.downgrading_tower.read().unwrap(), Some(false));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_build_tower_at_max_towers() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components allowing only 2 towers
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            world.set_max_towers(2);
+            world.set_cursor((40, 1));
+            world.set_upgrading_tower(true);
+            world.set_points(15);
+
+            // Add 2 towers
+            world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (0, 1));
+            world.add_broken_tower((1, 1));
+        }
+
+        upgrade_tower(world.clone());
+
+        // Make sure no tower was built
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap().len(), 2);
+        assert_eq!(*read_world.points.read().unwrap(), Some(15));
+    }
+
+    #[test]
+    fn test_downgrade_tower_not_downgrading_tower() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+            world.set_points(10);
+
+            // Add a base tower
+            world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (0, 1));
+        }
+
+        downgrade_tower(world.clone());
+
+        // Make sure the tower wasn't sold
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("base")));
+        assert_eq!(*read_world.points.read().unwrap(), Some(10));
+    }
+
+    #[test]
+    fn test_sell_first_tower() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+            world.set_points(10);
+            world.set_cursor((0, 1));
+            world.set_downgrading_tower(true);
+
+            // Add a base tower
+            world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (0, 1));
+        }
+
+        downgrade_tower(world.clone());
+
+        // Make sure the tower was sold
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::broken()));
+        assert_eq!(read_world.tower_tile.read().unwrap()[0], Some((0, 1)));
+        assert_eq!(read_world.sprite.read().unwrap()[0], Some(String::from("-")));
+        assert_eq!(*read_world.points.read().unwrap(), Some(17));
+        assert_eq!(*read_world.downgrading_tower.read().unwrap(), Some(false));
+    }
+
+    #[test]
+    fn test_sell_upgraded_tower_refunds_last_upgrade() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components with the cursor on a free buildable tile
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            world.set_cursor((40, 1));
+            world.set_points(30);
+        }
+
+        // Build a base tower and upgrade it into a second tower
+        for _ in 0..2 {
+            world.write().unwrap().set_upgrading_tower(true);
+            upgrade_tower(world.clone());
+        }
+        assert_eq!(world.read().unwrap().invested_points.read().unwrap()[0], Some(30));
+
+        world.write().unwrap().set_downgrading_tower(true);
+        downgrade_tower(world.clone());
+
+        // Make sure only the points spent on the second tower were refunded
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("base")));
+        assert_eq!(read_world.invested_points.read().unwrap()[0], Some(10));
+        assert_eq!(*read_world.points.read().unwrap(), Some(15));
+    }
+
+    #[test]
+    fn test_demolish_tower() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components with the cursor on a final tower
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            world.set_points(0);
+            world.set_cursor((40, 1));
+            world.set_demolishing_tower(true);
+            let _ = world.add_catalog_tower(TowerType::from("final"), TowerTarget::First, (40, 1));
+        }
+
+        demolish_tower(world.clone());
+
+        // Make sure the tower was turned straight back into a broken tower, refunding 75% of its 210 points
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::broken()));
+        assert_eq!(read_world.invested_points.read().unwrap()[0], Some(0));
+        assert_eq!(*read_world.points.read().unwrap(), Some(157));
+        assert_eq!(*read_world.demolishing_tower.read().unwrap(), Some(false));
+    }
+
+    #[test]
+    fn test_demolish_tower_with_full_refund() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components refunding every invested point
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            world.set_refund_percent(100);
+            world.set_points(0);
+            world.set_cursor((40, 1));
+            world.set_demolishing_tower(true);
+            let _ = world.add_catalog_tower(TowerType::from("sniper"), TowerTarget::First, (40, 1));
+        }
+
+        demolish_tower(world.clone());
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::broken()));
+        assert_eq!(*read_world.points.read().unwrap(), Some(65));
+    }
+
+    #[test]
+    fn test_sell_tower() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+            world.set_points(10);
+            world.set_cursor((2, 1));
+            world.set_downgrading_tower(true);
+
+            // Add 3 base towers
+            world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (0, 1));
+            world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (1, 1));
+            world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (2, 1));
+        }
+
+        downgrade_tower(world.clone());
+
+        // Make sure the tower was sold
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[2], Some(TowerType::broken()));
+        assert_eq!(read_world.tower_tile.read().unwrap()[2], Some((2, 1)));
+        assert_eq!(read_world.sprite.read().unwrap()[2], Some(String::from("-")));
+        assert_eq!(*read_world.points.read().unwrap(), Some(17));
+        assert_eq!(*read_world.downgrading_tower.read().unwrap(), Some(false));
+    }
+
+    #[test]
+    fn test_base_tower_does_not_attack_while_paused() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components and pause the game
+            world.initialize_singular_components(100);
+            world.set_game_phase(GamePhase::Paused);
+
+            // Add a base tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_tower(TowerType::from("base"), TowerTarget::First, (12, 1), String::from("!"));
+
+            // Add a base enemy
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![12]);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure the enemy wasn't damaged
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::default()));
+    }
+
+    #[test]
+    fn test_upgrade_tower_while_paused() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components and pause the game
+            world.initialize_singular_components(100);
+            world.set_game_phase(GamePhase::Paused);
+            world.set_cursor((0, 1));
+            world.set_upgrading_tower(true);
+            world.set_points(10);
+
+            // Add a broken tower
+            let _ = world.add_broken_tower((0, 1));
+        }
+
+        upgrade_tower(world.clone());
+
+        // Make sure the tower wasn't upgraded and the request was dropped
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::broken()));
+        assert_eq!(*read_world.points.read().unwrap(), Some(10));
+        assert_eq!(*read_world.upgrading_tower.read().unwrap(), Some(false));
+    }
+
+    #[test]
+    fn test_sell_tower_while_paused() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components and pause the game
+            world.initialize_singular_components(100);
+            world.set_game_phase(GamePhase::Paused);
+            world.set_points(10);
+            world.set_cursor((0, 1));
+            world.set_downgrading_tower(true);
+
+            // Add a base tower
+            world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (0, 1));
+        }
+
+        downgrade_tower(world.clone());
+
+        // Make sure the tower wasn't sold and the request was dropped
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.tower_type.read().unwrap()[0], Some(TowerType::from("base")));
+        assert_eq!(*read_world.points.read().unwrap(), Some(10));
+        assert_eq!(*read_world.downgrading_tower.read().unwrap(), Some(false));
+    }
+
+    #[test]
+    fn test_tower_waits_for_fire_interval_between_attacks() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a base tower (1 damage per 1000 milliseconds) next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (5, 1));
+
+            // Add a base enemy with a lot of health
+            let _ = world.add_enemy(EnemyType::from("base"), String::from("X"), 5, 100);
+        }
+
+        // Attack once and then wait for one tick less than the fire interval
+        for _ in 0..(1000 / TICK_MILLISECONDS) {
+            tower_attack_ai(world.clone());
+        }
+        assert_eq!(world.read().unwrap().health_change.read().unwrap()[1], Some(HealthChange::damage(1)));
+
+        // The tower has reloaded after the fire interval
+        tower_attack_ai(world.clone());
+        assert_eq!(world.read().unwrap().health_change.read().unwrap()[1], Some(HealthChange::damage(2)));
+    }
+
+    #[test]
+    fn test_tower_deals_catalog_damage() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a third tower (3 pierce damage per attack) next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("third"), TowerTarget::First, (5, 1));
+
+            // Add a base enemy with a lot of health
+            let _ = world.add_enemy(EnemyType::from("base"), String::from("X"), 5, 100);
+        }
+
+        // The shot flies the tile to the enemy in two ticks
+        tower_attack_ai(world.clone());
+        move_projectiles(world.clone());
+        move_projectiles(world.clone());
+
+        // Make sure the enemy took 3 pierce damage
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::damage(3)));
+        assert_eq!(read_world.attack_cooldown.read().unwrap()[0], Some(1000));
+    }
+
+    #[test]
+    fn test_armor_blocks_every_hit_in_a_tick() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components with a final enemy (20 health, 2 armor, 25% resistance)
+            world.initialize_singular_components(100);
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_enemy(EnemyType::from("final"), 5);
+        }
+
+        // Hit the enemy three times for 3 physical damage and once for 4 magic damage in the same tick
+        let mut definition = TowerCatalog::default().get(&TowerType::from("base")).clone();
+        definition.damage = 3;
+        {
+            let read_world = world.read().unwrap();
+            for _ in 0..3 {
+                hit_enemy(&read_world, &definition, 0, 100);
+            }
+            definition.damage = 4;
+            definition.damage_type = DamageType::Magic;
+            hit_enemy(&read_world, &definition, 0, 100);
+        }
+        resolve_health_change(world.clone());
+
+        // Make sure armor blocked 2 damage from each physical hit (the same as hits in separate ticks)
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health.read().unwrap()[0], Some(14));
+    }
+
+    #[test]
+    fn test_broken_tower_does_not_attack() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a broken tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_tower(TowerType::broken(), TowerTarget::First, (5, 1), String::from("-"));
+
+            // Add a base enemy
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![5]);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure the enemy wasn't damaged
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::default()));
+    }
+
+    #[test]
+    fn test_splash_tower_damages_enemies_near_target() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a splash tower (splash radius of 1) next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("splash"), TowerTarget::First, (20, 1));
+
+            // Add enemies on, next to, and two tiles away from the targeted enemy
+            let _ = world.add_catalog_enemies(EnemyType::from("base"), vec![19, 19, 20, 21]);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure every enemy within 1 tile of the target took magic damage
+        let hit = Some(HealthChange::damage(1));
+        let read_world = world.read().unwrap();
+        assert_eq!(
+            *read_world.health_change.read().unwrap(),
+            vec![None, hit, hit, hit, Some(HealthChange::default())],
+        );
+    }
+
+    #[test]
+    fn test_frost_tower_slows_target() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a frost tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("frost"), TowerTarget::First, (20, 1));
+
+            // Add an already slowed enemy in range
+            let enemy_id = world.add_catalog_enemy(EnemyType::from("base"), 20);
+            world.set_status_effects(enemy_id, vec![StatusEffect::Slow { percent: 50, duration: 50 }]);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure the slow was refreshed rather than stacked
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::damage(1)));
+        assert_eq!(
+            read_world.status_effects.read().unwrap()[1],
+            Some(vec![StatusEffect::Slow { percent: 50, duration: 2000 }]),
+        );
+    }
+
+    #[test]
+    fn test_poison_tower_stacks_poison() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a poison tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("poison"), TowerTarget::First, (20, 1));
+
+            // Add an already poisoned enemy in range
+            let enemy_id = world.add_catalog_enemy(EnemyType::from("final"), 20);
+            world.set_status_effects(enemy_id, vec![StatusEffect::Poison { damage: 1, duration: 500 }]);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure a second stack of poison was added
+        let read_world = world.read().unwrap();
+        assert_eq!(
+            read_world.status_effects.read().unwrap()[1],
+            Some(vec![
+                StatusEffect::Poison { damage: 1, duration: 500 },
+                StatusEffect::Poison { damage: 1, duration: 4000 },
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_tower_skips_undetected_enemies() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a tower next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (20, 1));
+
+            // Add an undetected ghost in front of a regular enemy
+            let _ = world.add_catalog_enemy(EnemyType::from("ghost"), 20);
+            let _ = world.add_catalog_enemy(EnemyType::from("base"), 21);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure the tower attacked the regular enemy instead of the ghost
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[1], Some(HealthChange::default()));
+        assert_eq!(read_world.health_change.read().unwrap()[2], Some(HealthChange::damage(1)));
+    }
+
+    #[test]
+    fn test_ground_tower_skips_flying_enemies() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Add a base tower (ground only) and a second tower (ground and air) next to a straight path
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (20, 1));
+            let _ = world.add_catalog_tower(TowerType::from("second"), TowerTarget::First, (21, 1));
+
+            // Add a flyer in front of a regular enemy
+            let _ = world.add_catalog_enemy(EnemyType::from("flyer"), 20);
+            let _ = world.add_catalog_enemy(EnemyType::from("base"), 21);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure only the second tower attacked the flyer
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[2], Some(HealthChange::damage(1)));
+        assert_eq!(read_world.health_change.read().unwrap()[3], Some(HealthChange::damage(1)));
+    }
+
+    #[test]
+    fn test_critical_hits_and_dodges() {
+        let world = TowerDefenseWorld::new();
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Initialize Singular Components
+            world.initialize_singular_components(100);
+
+            // Make base towers always crit and second enemies always dodge
+            let mut tower_catalog = TowerCatalog::default();
+            tower_catalog.towers.get_mut(&TowerType::from("base")).unwrap().crit_chance = 100;
+            world.set_tower_catalog(tower_catalog);
+            let mut enemy_catalog = EnemyCatalog::default();
+            enemy_catalog.enemies.get_mut(&EnemyType::from("second")).unwrap().dodge_chance = 100;
+            world.set_enemy_catalog(enemy_catalog);
+
+            // Add a base tower next to a base enemy and another next to a second enemy
+            world.set_map(Map::straight(100));
+            let _ = world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (20, 1));
+            let _ = world.add_catalog_tower(TowerType::from("base"), TowerTarget::First, (40, 1));
+            let _ = world.add_catalog_enemy(EnemyType::from("base"), 20);
+            let _ = world.add_catalog_enemy(EnemyType::from("second"), 40);
+        }
+
+        tower_attack_ai(world.clone());
+
+        // Make sure the base enemy took double damage and the second enemy took none
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.health_change.read().unwrap()[2], Some(HealthChange::damage(2)));
+        assert_eq!(read_world.health_change.read().unwrap()[3], Some(HealthChange::default()));
+    }
+}
@@ -3,7 +3,8 @@
 //! 
 
 use std::io::{self, stdout, Stdout};
-use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use nate_engine::Renderer;
@@ -20,25 +21,156 @@ use ratatui::{
     widgets::{canvas::Canvas, Block, Borders},
 };
 
-use crate::{TowerDefenseWorld, TowerTarget, TOTAL_POSITIONS, TOTAL_TOWERS, TOWER_SEPARATION};
+use crate::{GamePhase, Layer, StatusEffect, TowerDefenseWorld, TowerTarget, TowerType, WaveIncome};
+use crate::catalog::tower_catalog::TowerCatalog;
+use crate::world::map::{Map, Tile};
 
-fn set_tower_target(target_tower: u32, target: TowerTarget, mut target_enemies: RwLockWriteGuard<Vec<Option<TowerTarget>>>) {
-    let mut current_tower = 0;
-    for target_enemy in target_enemies.iter_mut().filter(|v| v.is_some()) {
-        if current_tower == target_tower {
-            *target_enemy = Some(target);
-            return;
+/// The width of the canvas
+const CANVAS_WIDTH: f64 = 100.0;
+/// The bottom and top of the canvas
+const CANVAS_BOTTOM: f64 = -25.0;
+const CANVAS_TOP: f64 = 24.0;
+/// The height of the canvas the map is drawn in (centered vertically)
+const MAP_HEIGHT: f64 = 40.0;
+/// The height on the canvas of the boss health bar (above the map)
+const BOSS_BAR_Y: f64 = 23.0;
+/// The number of characters in the boss health bar
+const BOSS_BAR_WIDTH: usize = 40;
+// The keys that upgrade a tower into its first, second, and third branch
+const BRANCH_KEYS: [char; 3] = ['e', 'f', 'g'];
+
+/// The canvas position a map tile is drawn at
+fn tile_position(map: &Map, (x, y): (u32, u32)) -> (f64, f64) {
+    (
+        x as f64 * CANVAS_WIDTH / map.width as f64,
+        MAP_HEIGHT / 2.0 - y as f64 * MAP_HEIGHT / map.height as f64,
+    )
+}
+
+/// The canvas distance covered by one column and one row of characters inside the border of an area
+fn character_size(area: Rect) -> (f64, f64) {
+    (
+        CANVAS_WIDTH / area.width.saturating_sub(3).max(1) as f64,
+        (CANVAS_TOP - CANVAS_BOTTOM) / area.height.saturating_sub(3).max(1) as f64,
+    )
+}
+
+/// The distance above a tower its labels are drawn: halfway to the row of tiles above, as long as
+/// there is a row of characters free between the rows of tiles
+fn tower_label_offset(map: &Map, character_height: f64) -> Option<f64> {
+    let tile_height = MAP_HEIGHT / map.height as f64;
+    (tile_height >= 2.0 * character_height).then_some(tile_height / 2.0)
+}
+
+/// Whether a label drawn at a tile stays clear of the tiles beside it
+fn label_fits(map: &Map, character_width: f64, label: &str) -> bool {
+    label.chars().count() as f64 * character_width <= CANVAS_WIDTH / map.width as f64
+}
+
+/// A bar showing how much health a boss has left (and its shield)
+fn boss_health_bar(health: u32, max_health: u32, shield: u32) -> String {
+    let filled = (health.min(max_health) as usize * BOSS_BAR_WIDTH).div_ceil(max_health.max(1) as usize);
+    let mut bar = format!(
+        "BOSS [{}{}] {}/{}",
+        "#".repeat(filled),
+        "-".repeat(BOSS_BAR_WIDTH - filled),
+        health,
+        max_health,
+    );
+    if shield > 0 {
+        bar.push_str(&format!(" (+{} shield)", shield));
+    }
+    bar
+}
+
+/// A short name for the enemy a tower targets
+fn target_label(target_enemy: TowerTarget) -> &'static str {
+    match target_enemy {
+        TowerTarget::First => "1st",
+        TowerTarget::Second => "2nd",
+        TowerTarget::Last => "last",
+        TowerTarget::Strongest => "str",
+        TowerTarget::Weakest => "weak",
+        TowerTarget::Fastest => "fast",
+        TowerTarget::Closest => "near",
+    }
+}
+
+/// The cost to upgrade a tower (followed by the cost of each of its branches)
+fn upgrade_label(tower_catalog: &TowerCatalog, tower_type: &TowerType) -> String {
+    let mut label = format!("{}", tower_catalog.upgrade_price(tower_type));
+    for branch in 0..tower_catalog.branches(tower_type).len() {
+        label.push_str(&format!("/{}", tower_catalog.branch_price(tower_type, branch)));
+    }
+    label
+}
+
+/// The key, name, and cost of every tower a tower can be upgraded or branched into
+fn upgrade_options_label(tower_catalog: &TowerCatalog, tower_type: &TowerType) -> String {
+    let upgrade = tower_catalog.upgrade(tower_type)
+        .map(|upgrade| ('w', upgrade, tower_catalog.upgrade_price(tower_type)));
+    let branches = BRANCH_KEYS.iter()
+        .zip(tower_catalog.branches(tower_type))
+        .enumerate()
+        .map(|(index, (key, branch))| (*key, branch, tower_catalog.branch_price(tower_type, index)));
+    upgrade.into_iter()
+        .chain(branches)
+        .map(|(key, tower_type, cost)| format!("{}: {} {}", key, tower_type.0, cost))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// The targeting mode of the selected tower (and what the tower or the tile under the cursor can be
+/// upgraded or built into, when there is no room to draw it above the tile) for the status line
+fn selected_tower_label(world: &TowerDefenseWorld, upgrade_options: bool) -> String {
+    let tower_catalog = world.tower_catalog.read().unwrap();
+    let tower_catalog = tower_catalog.as_ref().unwrap();
+    let mut label = String::new();
+    let tower_type = match world.selected_tower() {
+        Some(entity_id) => {
+            let tower_type = world.tower_type.read().unwrap()[entity_id].clone().unwrap();
+            let target_enemy = world.target_enemy.read().unwrap()[entity_id];
+            if let Some(target_enemy) = target_enemy.filter(|_| tower_catalog.get(&tower_type).damage > 0) {
+                label.push_str(&format!(" ----- Target: {}", target_label(target_enemy)));
+            }
+            Some(tower_type)
+        },
+        None => {
+            let cursor = world.cursor.read().unwrap().unwrap();
+            let buildable = world.map.read().unwrap().as_ref().unwrap().tile(cursor) == Tile::Buildable;
+            buildable.then(TowerType::broken)
+        },
+    };
+    if let Some(tower_type) = tower_type.filter(|_| upgrade_options) {
+        let options = upgrade_options_label(tower_catalog, &tower_type);
+        if !options.is_empty() {
+            label.push_str(&format!(" ----- {}", options));
         }
-        current_tower += 1;
     }
+    label
+}
+
+/// The points paid out at the end of the current wave (if it ended now)
+fn wave_income_label(wave_income: WaveIncome) -> String {
+    format!(
+        "Wave End Income: +{} ----- Interest: +{} ----- No Damage Bonus: +{} ----- Banks: +{}",
+        wave_income.total(),
+        wave_income.interest,
+        wave_income.clear_bonus,
+        wave_income.tower_income,
+    )
 }
 
 pub struct TowerDefenseRenderer {
     terminal: Terminal<CrosstermBackend<Stdout>>,
+    // The file the game is saved to
+    save_file: PathBuf,
+    // The result of the last save (shown at the bottom of the screen)
+    save_message: Option<String>,
 }
 
 impl TowerDefenseRenderer {
-    pub fn new() -> io::Result<Self> {
+    pub fn new(save_file: PathBuf) -> io::Result<Self> {
         stdout().execute(EnterAlternateScreen)?;
         enable_raw_mode()?;
         let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
@@ -46,6 +178,8 @@ impl TowerDefenseRenderer {
 
         Ok(Self {
             terminal,
+            save_file,
+            save_message: None,
         })
     }
 }
@@ -62,47 +196,109 @@ impl Renderer<TowerDefenseWorld> for TowerDefenseRenderer {
 
     fn render(&mut self, world: Arc<RwLock<TowerDefenseWorld>>) -> Result<(), Self::Error> {
         let world = world.read().unwrap();
+        let save_message = self.save_message.clone();
 
         let _err = self.terminal.draw(|frame| {
             let area = frame.size();
+            let character_size = character_size(area);
+            let label_offset = {
+                let map = world.map.read().unwrap();
+                tower_label_offset(map.as_ref().unwrap(), character_size.1)
+            };
             frame.render_widget(
                 Canvas::default()
                     .block(
                         Block::default()
                         .borders(Borders::ALL)
-                        .title(
-                            format!(
-                                "Tower Defense tui ----- Base Health: {} ----- Alive Enemies: {} ----- Points: {} ----- Level: {}",
+                        .title(Line::from(vec![
+                            Span::raw(format!(
+                                "Tower Defense tui ----- Base Health: {} ----- Alive Enemies: {} ----- Points: {} ----- Level: {} ----- Towers: {}/{}",
                                 (*world.base_health.read().unwrap()).unwrap(),
                                 (*world.alive_enemies.read().unwrap()).unwrap(),
                                 (*world.points.read().unwrap()).unwrap(),
                                 (*world.level.read().unwrap()).unwrap(),
-                            )
-                        )
+                                world.total_towers(),
+                                (*world.max_towers.read().unwrap()).unwrap(),
+                            )),
+                            Span::raw(selected_tower_label(&world, label_offset.is_none())),
+                            // Show the game is paused in the status line rather than over the map
+                            match world.game_phase.read().unwrap().unwrap() {
+                                GamePhase::Paused => " ----- PAUSED".yellow().bold(),
+                                _ => Span::raw(""),
+                            },
+                        ]))
+                        .title_bottom(wave_income_label(world.wave_income()))
                     )
                     .background_color(Color::Black)
-                    .x_bounds([0.0, TOTAL_POSITIONS as f64])
-                    .y_bounds([-25.0, 24.0])
+                    .x_bounds([0.0, CANVAS_WIDTH])
+                    .y_bounds([CANVAS_BOTTOM, CANVAS_TOP])
                     .paint(|ctx| {
-                        if world.help_displayed.read().unwrap().unwrap() {
+                        if world.game_phase.read().unwrap().unwrap() == GamePhase::GameOver {
+                            ctx.print(40.0, 9.0, "GAME OVER".red().bold());
+                            ctx.print(
+                                30.0,
+                                0.0,
+                                format!(
+                                    "Final Level: {}     Points: {}     Enemies Killed: {}",
+                                    (*world.level.read().unwrap()).unwrap(),
+                                    (*world.points.read().unwrap()).unwrap(),
+                                    (*world.enemies_killed.read().unwrap()).unwrap(),
+                                ).white(),
+                            );
+                            ctx.print(34.0, -10.0, "r - restart     q - quit".green());
+                        } else if world.help_displayed.read().unwrap().unwrap() {
                             ctx.print(0.0, 9.0, "
-                                q - quit                               d (right arrow) - move tower selection right     a (left arrow) - move tower selection left\n
+                                q - quit                               arrow keys - move cursor                         a / d - move cursor to previous/next tower\n
                             ".green());
                             ctx.print(0.0, 0.0, "
-                                w (up arrow) - upgrade tower           s (down arrow) - sell tower                      1 - make tower attack first enemy\n
+                                w - build/upgrade tower                e / f / g - build/upgrade into a branch           s / x - sell/demolish tower at cursor\n
                             ".green());
                             ctx.print(0.0, -10.0, "
-                                2 - make tower attack second enemy     3 - make tower attack last enemy                 h - toggle help\n
+                                1 / 2 / 3 - attack first/second/last   4 / 5 - attack strongest/weakest                 6 / 7 - attack fastest/closest to base\n
+                            ".green());
+                            ctx.print(0.0, -20.0, "
+                                p (space) - pause/resume game          r - restart (once the base is destroyed)         S - save game     h - toggle help\n
                             ".green());
                         } else {
-                            let mut tower_num = 0;
+                            let map_ref = world.map.read().unwrap();
+                            let map = map_ref.as_ref().unwrap();
+
+                            // Draw the path and the buildable tiles
+                            for y in 0..map.height {
+                                for x in 0..map.width {
+                                    let (canvas_x, canvas_y) = tile_position(map, (x, y));
+                                    match map.tile((x, y)) {
+                                        Tile::Path => ctx.print(canvas_x, canvas_y, "=".dark_gray()),
+                                        Tile::Buildable => ctx.print(canvas_x, canvas_y, ".".dark_gray()),
+                                        Tile::Empty => (),
+                                    }
+                                }
+                            }
+                            let (spawn_x, spawn_y) = tile_position(map, map.path[0]);
+                            ctx.print(spawn_x, spawn_y, "S".red());
+                            let (base_x, base_y) = tile_position(map, map.path[map.path.len() - 1]);
+                            ctx.print(base_x, base_y, "B".blue().bold());
+
+                            let selected_tower = world.selected_tower();
+                            let cursor = world.cursor.read().unwrap().unwrap();
+                            let tower_catalog_ref = world.tower_catalog.read().unwrap();
+                            let tower_catalog = tower_catalog_ref.as_ref().unwrap();
                             let sprite = world.sprite.read().unwrap();
                             let tower_type = world.tower_type.read().unwrap();
+                            let tower_tile = world.tower_tile.read().unwrap();
                             let health = world.health.read().unwrap();
                             let enemy_position = world.enemy_position.read().unwrap();
+                            let enemy_tile = world.enemy_tile.read().unwrap();
+                            let enemy_type = world.enemy_type.read().unwrap();
+                            let status_effects = world.status_effects.read().unwrap();
+                            let temporary_shield = world.temporary_shield.read().unwrap();
+                            let detected = world.detected.read().unwrap();
+                            let enemy_layer = world.enemy_layer.read().unwrap();
+                            let enemy_catalog_ref = world.enemy_catalog.read().unwrap();
+                            let enemy_catalog = enemy_catalog_ref.as_ref().unwrap();
     
                             for ((((
-                                _entity_id,
+                                entity_id,
                                 sprite),
                                 tower_type),
                                 health),
@@ -112,60 +308,105 @@ impl Renderer<TowerDefenseWorld> for TowerDefenseRenderer {
                                 .zip(health.iter())
                                 .zip(enemy_position.iter()).filter(|v| v.0.0.0.1.is_some()) {
                                 let sprite = sprite.as_ref().unwrap();
-                                if tower_type.is_some() {
-                                    // Draw Upgrade Cost Above Tower
-                                    if tower_num == world.selected_tower.read().unwrap().unwrap() {
-                                        ctx.print(
-                                            TOWER_SEPARATION as f64 * (tower_num as f64 + 0.5),
-                                            if tower_num % 2 == 0 { 12.0 } else { -12.0 },
-                                            sprite.clone().white().bold().underlined(),
-                                        );
-                                        ctx.print(
-                                            TOWER_SEPARATION as f64 * (tower_num as f64 + 0.5),
-                                            if tower_num % 2 == 0 { 16.0 } else { -17.0 },
-                                            format!("{}", tower_type.unwrap().upgrade_price()).bold(),
-                                        )
+                                if let (Some(tower_type), Some(tile)) = (tower_type, tower_tile[entity_id]) {
+                                    // Draw Upgrade Costs Above Towers (where there is room for them)
+                                    let (x, y) = tile_position(map, tile);
+                                    if selected_tower == Some(entity_id) {
+                                        ctx.print(x, y, sprite.clone().white().bold().underlined());
+                                        if let Some(label_offset) = label_offset {
+                                            ctx.print(
+                                                x,
+                                                y + label_offset,
+                                                upgrade_options_label(tower_catalog, tower_type).bold(),
+                                            );
+                                        }
                                     } else {
-                                        ctx.print(
-                                            TOWER_SEPARATION as f64 * (tower_num as f64 + 0.5),
-                                            if tower_num % 2 == 0 { 12.0 } else { -12.0 },
-                                            sprite.clone().white(),
-                                        );
-                                        ctx.print(
-                                            TOWER_SEPARATION as f64 * (tower_num as f64 + 0.5),
-                                            if tower_num % 2 == 0 { 16.0 } else { -17.0 },
-                                            format!("{}", tower_type.unwrap().upgrade_price()),
-                                        );
+                                        ctx.print(x, y, sprite.clone().white());
+                                        let label = upgrade_label(tower_catalog, tower_type);
+                                        if let Some(label_offset) = label_offset.filter(|_| label_fits(map, character_size.0, &label)) {
+                                            ctx.print(x, y + label_offset, label);
+                                        }
                                     }
-                                    tower_num += 1;
-                                } else if let (Some(position), Some(health)) = (enemy_position, health) {
-                                    if *position < TOTAL_POSITIONS {
-                                        match *health {
-                                            0 => (),
-                                            1 => ctx.print(*position as f64, 0.0, sprite.clone().red()),
-                                            2 => ctx.print(*position as f64, 0.0, sprite.clone().light_red()),
-                                            3 => ctx.print(*position as f64, 0.0, sprite.clone().light_yellow()),
-                                            4 => ctx.print(*position as f64, 0.0, sprite.clone().yellow()),
-                                            5 => ctx.print(*position as f64, 0.0, sprite.clone().light_green()),
-                                            6 => ctx.print(*position as f64, 0.0, sprite.clone().green()),
-                                            7 => ctx.print(*position as f64, 0.0, sprite.clone().light_blue()),
-                                            8 => ctx.print(*position as f64, 0.0, sprite.clone().blue()),
-                                            9 => ctx.print(*position as f64, 0.0, sprite.clone().light_cyan()),
-                                            10 => ctx.print(*position as f64, 0.0, sprite.clone().cyan()),
-                                            11 => ctx.print(*position as f64, 0.0, sprite.clone().light_magenta()),
-                                            12 => ctx.print(*position as f64, 0.0, sprite.clone().magenta()),
-                                            13 => ctx.print(*position as f64, 0.0, sprite.clone().gray()),
-                                            14 => ctx.print(*position as f64, 0.0, sprite.clone().white()),
-                                            15 => ctx.print(*position as f64, 0.0, sprite.clone().bold().red()),
-                                            16 => ctx.print(*position as f64, 0.0, sprite.clone().bold().yellow()),
-                                            17 => ctx.print(*position as f64, 0.0, sprite.clone().bold().green()),
-                                            18 => ctx.print(*position as f64, 0.0, sprite.clone().bold().blue()),
-                                            19 => ctx.print(*position as f64, 0.0, sprite.clone().bold().cyan()),
-                                            _ => ctx.print(*position as f64, 0.0, sprite.clone().bold().magenta()),
+                                } else if let (Some(position), Some(health), Some(tile)) = (enemy_position, health, enemy_tile[entity_id]) {
+                                    if *position < map.path_length() && *health > 0 {
+                                        let enemy_type = enemy_type[entity_id].as_ref().unwrap();
+                                        let color = enemy_catalog.get(enemy_type).color;
+                                        let (x, y) = tile_position(map, tile);
+                                        // Draw flying enemies a row of characters above walking enemies
+                                        let y = match enemy_layer[entity_id] {
+                                            Some(Layer::Air) => y + character_size.1,
+                                            _ => y,
+                                        };
+
+                                        // Highlight frozen, slowed, and poisoned enemies
+                                        let effects = status_effects[entity_id].as_deref().unwrap_or_default();
+                                        let sprite = sprite.clone().fg(color);
+
+                                        // Embolden enemies shielded by shield-bearers
+                                        let sprite = match temporary_shield[entity_id] {
+                                            Some(shield) if shield.amount > 0 => sprite.bold(),
+                                            _ => sprite,
+                                        };
+
+                                        // Dim stealth enemies no detector can see
+                                        let sprite = match detected[entity_id] {
+                                            Some(false) => sprite.dim(),
+                                            _ => sprite,
+                                        };
+                                        if effects.iter().any(|v| matches!(v, StatusEffect::Freeze { .. })) {
+                                            ctx.print(x, y, sprite.on_light_cyan());
+                                        } else if effects.iter().any(|v| matches!(v, StatusEffect::Slow { .. })) {
+                                            ctx.print(x, y, sprite.on_blue());
+                                        } else if effects.iter().any(|v| matches!(v, StatusEffect::Poison { .. })) {
+                                            ctx.print(x, y, sprite.on_green());
+                                        } else {
+                                            ctx.print(x, y, sprite);
                                         }
                                     }
                                 }
                             }   
+
+                            // Draw the shots flying from towers to enemies
+                            for projectile in world.projectile.read().unwrap().iter().flatten() {
+                                let (origin_x, origin_y) = tile_position(map, projectile.origin);
+                                let (target_x, target_y) = tile_position(map, projectile.target_tile);
+                                let progress = projectile.progress();
+                                ctx.print(
+                                    origin_x + (target_x - origin_x) * progress,
+                                    origin_y + (target_y - origin_y) * progress,
+                                    "•".yellow(),
+                                );
+                            }
+
+                            // Draw the health bar of the first boss on the map across the top of the canvas
+                            let shield = world.shield.read().unwrap();
+                            let boss = enemy_type.iter().enumerate().find_map(|(entity_id, enemy_type)| {
+                                let definition = enemy_catalog.get(enemy_type.as_ref()?);
+                                let health = health[entity_id]?;
+                                let on_map = enemy_position[entity_id]? < map.path_length();
+                                (definition.is_boss() && health > 0 && on_map)
+                                    .then(|| (health, definition.health, shield[entity_id].unwrap_or(0)))
+                            });
+                            if let Some((health, max_health, shield)) = boss {
+                                ctx.print(0.0, BOSS_BAR_Y, boss_health_bar(health, max_health, shield).light_magenta().bold());
+                            }
+
+                            // Draw the placement cursor (with the build cost on buildable tiles)
+                            if selected_tower.is_none() {
+                                let (x, y) = tile_position(map, cursor);
+                                ctx.print(x, y, "+".yellow().bold());
+                                if let Some(label_offset) = label_offset.filter(|_| map.tile(cursor) == Tile::Buildable) {
+                                    ctx.print(
+                                        x,
+                                        y + label_offset,
+                                        upgrade_options_label(tower_catalog, &TowerType::broken()).yellow(),
+                                    );
+                                }
+                            }
+                        }
+
+                        if let Some(save_message) = save_message.as_ref() {
+                            ctx.print(0.0, -24.0, save_message.clone().yellow());
                         }
                     }),
                 area
@@ -178,43 +419,63 @@ impl Renderer<TowerDefenseWorld> for TowerDefenseRenderer {
                 if key.kind == KeyEventKind::Press {
                     match key.code {
                         KeyCode::Char('q') => return Err("Leaving Render Thread".into()),
-                        KeyCode::Char('a') | KeyCode::Left => {
-                            // Retreat the selected tower
-                            let selected_tower = world.selected_tower.read().unwrap().unwrap();
-                            if selected_tower == 0 {
-                                *world.selected_tower.write().unwrap() = Some(TOTAL_TOWERS - 1);
-                            } else {
-                                *world.selected_tower.write().unwrap() = Some(selected_tower - 1);
-                            }
+                        KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down => {
+                            // Move the placement cursor (staying on the map)
+                            let (width, height) = {
+                                let map = world.map.read().unwrap();
+                                let map = map.as_ref().unwrap();
+                                (map.width, map.height)
+                            };
+                            let (x, y) = world.cursor.read().unwrap().unwrap();
+                            let cursor = match key.code {
+                                KeyCode::Left => (x.saturating_sub(1), y),
+                                KeyCode::Right => ((x + 1).min(width - 1), y),
+                                KeyCode::Up => (x, y.saturating_sub(1)),
+                                _ => (x, (y + 1).min(height - 1)),
+                            };
+                            *world.cursor.write().unwrap() = Some(cursor);
                         },
-                        KeyCode::Char('d') | KeyCode::Right => {
-                            // Advance the selected tower
-                            let selected_tower = world.selected_tower.read().unwrap().unwrap();
-                            *world.selected_tower.write().unwrap() = Some((selected_tower + 1) % TOTAL_TOWERS);
+                        KeyCode::Char('a') | KeyCode::Char('d') => {
+                            // Move the placement cursor to the previous or next tower
+                            let tower_tiles: Vec<(u32, u32)> = world.tower_tile.read().unwrap().iter().flatten().copied().collect();
+                            if !tower_tiles.is_empty() {
+                                let cursor = world.cursor.read().unwrap().unwrap();
+                                let next_tower = match (tower_tiles.iter().position(|v| *v == cursor), key.code) {
+                                    (Some(0), KeyCode::Char('a')) | (None, KeyCode::Char('a')) => tower_tiles.len() - 1,
+                                    (Some(tower), KeyCode::Char('a')) => tower - 1,
+                                    (Some(tower), _) => (tower + 1) % tower_tiles.len(),
+                                    (None, _) => 0,
+                                };
+                                *world.cursor.write().unwrap() = Some(tower_tiles[next_tower]);
+                            }
                         },
                         KeyCode::Char('1') => {
                             // Set the selected tower to target the first enemy
-                            set_tower_target(
-                                world.selected_tower.read().unwrap().unwrap(),
-                                TowerTarget::First,
-                                world.target_enemy.write().unwrap(),
-                            );
+                            world.set_selected_tower_target(TowerTarget::First);
                         },
                         KeyCode::Char('2') => {
                             // Set the selected tower to target the second enemy
-                            set_tower_target(
-                                world.selected_tower.read().unwrap().unwrap(),
-                                TowerTarget::Second,
-                                world.target_enemy.write().unwrap(),
-                            );
+                            world.set_selected_tower_target(TowerTarget::Second);
                         },
                         KeyCode::Char('3') => {
                             // Set the selected tower to target the last enemy
-                            set_tower_target(
-                                world.selected_tower.read().unwrap().unwrap(),
-                                TowerTarget::Last,
-                                world.target_enemy.write().unwrap(),
-                            );
+                            world.set_selected_tower_target(TowerTarget::Last);
+                        },
+                        KeyCode::Char('4') => {
+                            // Set the selected tower to target the enemy with the most health
+                            world.set_selected_tower_target(TowerTarget::Strongest);
+                        },
+                        KeyCode::Char('5') => {
+                            // Set the selected tower to target the enemy with the least health
+                            world.set_selected_tower_target(TowerTarget::Weakest);
+                        },
+                        KeyCode::Char('6') => {
+                            // Set the selected tower to target the fastest enemy
+                            world.set_selected_tower_target(TowerTarget::Fastest);
+                        },
+                        KeyCode::Char('7') => {
+                            // Set the selected tower to target the enemy nearest the base
+                            world.set_selected_tower_target(TowerTarget::Closest);
                         },
                         KeyCode::Char('h') => {
                             // Toggle Help on and off
@@ -224,12 +485,39 @@ impl Renderer<TowerDefenseWorld> for TowerDefenseRenderer {
                                 *world.help_displayed.write().unwrap() = Some(true);
                             }
                         },
-                        KeyCode::Char('w') | KeyCode::Up => {
+                        KeyCode::Char('w') => {
                             *world.upgrading_tower.write().unwrap() = Some(true);
                         },
-                        KeyCode::Char('s') | KeyCode::Down => {
+                        KeyCode::Char(key) if BRANCH_KEYS.contains(&key) => {
+                            let branch = BRANCH_KEYS.iter().position(|v| *v == key);
+                            *world.branching_tower.write().unwrap() = Some(branch);
+                        },
+                        KeyCode::Char('s') => {
                             *world.downgrading_tower.write().unwrap() = Some(true);
-                        }
+                        },
+                        KeyCode::Char('x') => {
+                            *world.demolishing_tower.write().unwrap() = Some(true);
+                        },
+                        KeyCode::Char('p') | KeyCode::Char(' ') => {
+                            // Toggle between playing and paused
+                            let game_phase = world.game_phase.read().unwrap().unwrap();
+                            match game_phase {
+                                GamePhase::Playing => *world.game_phase.write().unwrap() = Some(GamePhase::Paused),
+                                GamePhase::Paused => *world.game_phase.write().unwrap() = Some(GamePhase::Playing),
+                                GamePhase::GameOver => (),
+                            }
+                        },
+                        KeyCode::Char('r') if world.game_phase.read().unwrap().unwrap() == GamePhase::GameOver => {
+                            // Restart the game once the base has been destroyed
+                            *world.restarting_game.write().unwrap() = Some(true);
+                        },
+                        KeyCode::Char('S') => {
+                            // Save the running game
+                            self.save_message = Some(match world.save_game(&self.save_file) {
+                                Ok(()) => format!("Game saved to {}", self.save_file.display()),
+                                Err(err) => format!("Failed to save game: {}", err),
+                            });
+                        },
                         _ => (),
                     }
                 }
@@ -242,4 +530,49 @@ impl Renderer<TowerDefenseWorld> for TowerDefenseRenderer {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The row of characters the canvas draws a label at (the same rounding as the canvas)
+    fn character_row(area: Rect, y: f64) -> u16 {
+        ((CANVAS_TOP - y) * area.height.saturating_sub(3) as f64 / (CANVAS_TOP - CANVAS_BOTTOM)) as u16
+    }
+
+    #[test]
+    fn test_tower_labels_between_rows_of_tiles() {
+        let map = Map::default();
+        let area = Rect::new(0, 0, 120, 50);
+        let size = character_size(area);
+
+        // Labels are drawn on a row of characters of their own between adjacent rows of tiles
+        let label_offset = tower_label_offset(&map, size.1).unwrap();
+        let (_, tower_y) = tile_position(&map, (3, 2));
+        let (_, above_y) = tile_position(&map, (3, 1));
+        let label_row = character_row(area, tower_y + label_offset);
+        assert!(label_row < character_row(area, tower_y));
+        assert!(label_row > character_row(area, above_y));
+
+        // Short terminals have no room between the rows of tiles
+        assert_eq!(tower_label_offset(&map, character_size(Rect::new(0, 0, 120, 30)).1), None);
+
+        // Only labels as narrow as a tile fit beside the towers next to them
+        assert!(label_fits(&map, size.0, "10"));
+        assert!(!label_fits(&map, size.0, "10/15"));
+    }
+
+    #[test]
+    fn test_flying_enemies_above_tile_position() {
+        let map = Map::default();
+        for height in [20, 24, 30, 40, 50, 80] {
+            // Flying enemies are drawn on the row of characters above walking enemies on the same tile
+            let area = Rect::new(0, 0, 120, height);
+            for y in 0..map.height {
+                let (_, tile_y) = tile_position(&map, (0, y));
+                let flying_y = tile_y + character_size(area).1;
+                assert_eq!(character_row(area, flying_y) + 1, character_row(area, tile_y));
+            }
+        }
+    }
+}
@@ -1 +1,3 @@
-pub mod tower_defense_world;
\ No newline at end of file
+pub mod tower_defense_world;
+pub mod save;
+pub mod map;
\ No newline at end of file
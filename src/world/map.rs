@@ -0,0 +1,308 @@
+//!
+//! Maps of Enemy Paths and Buildable Tiles
+//! 
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// The map shipped with the game
+pub const DEFAULT_MAP: &str = include_str!("../../assets/map.txt");
+
+/// A single tile of a map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tile {
+    // Scenery that enemies can't walk on and towers can't be built on
+    Empty,
+    // Part of the path enemies walk along (including the spawn and the base)
+    Path,
+    // A tile towers can be built on
+    Buildable,
+}
+
+/// A grid of tiles with a single path from the enemy spawn to the base
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Map {
+    pub width: u32,
+    pub height: u32,
+    // The tiles of the map (row by row, starting at the top left)
+    pub tiles: Vec<Tile>,
+    // The tiles of the path from the enemy spawn to the base
+    pub path: Vec<(u32, u32)>,
+}
+
+/// Create an invalid map error
+fn invalid_map<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+impl Map {
+    /// Parse a map from the contents of a map file
+    ///
+    /// `.` is empty, `#` is buildable, `=` is path, `S` is the enemy spawn, and `B` is the base
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let rows: Vec<&str> = contents.lines().filter(|v| !v.trim().is_empty()).collect();
+        let width = rows.first().map(|v| v.chars().count()).unwrap_or(0) as u32;
+        let height = rows.len() as u32;
+        if width == 0 {
+            return Err(invalid_map("The map must have at least one tile"));
+        }
+
+        let mut tiles = Vec::with_capacity((width * height) as usize);
+        let mut spawn = None;
+        let mut base = None;
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() as u32 != width {
+                return Err(invalid_map(format!("Row {} of the map must be {} tiles wide", y + 1, width)));
+            }
+            for (x, tile) in row.chars().enumerate() {
+                let coordinates = (x as u32, y as u32);
+                tiles.push(match tile {
+                    '.' => Tile::Empty,
+                    '#' => Tile::Buildable,
+                    '=' => Tile::Path,
+                    'S' if spawn.is_none() => {
+                        spawn = Some(coordinates);
+                        Tile::Path
+                    },
+                    'B' if base.is_none() => {
+                        base = Some(coordinates);
+                        Tile::Path
+                    },
+                    'S' | 'B' => return Err(invalid_map(format!("The map must only have one '{}' tile", tile))),
+                    _ => return Err(invalid_map(format!("Unknown map tile '{}'", tile))),
+                });
+            }
+        }
+
+        let (Some(spawn), Some(base)) = (spawn, base) else {
+            return Err(invalid_map("The map must have an enemy spawn ('S') and a base ('B')"));
+        };
+
+        let mut map = Self {
+            width,
+            height,
+            tiles,
+            path: vec![spawn],
+        };
+        map.trace_path(base)?;
+        Ok(map)
+    }
+
+    /// Load a map from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// A map with a straight path of a given length along the top row and buildable
+    /// tiles along the row below it (the base is on the left and the spawn on the right)
+    pub fn straight(length: u32) -> Self {
+        let length = length.max(2);
+        let mut tiles = vec![Tile::Path; length as usize];
+        tiles.extend(vec![Tile::Buildable; length as usize]);
+        Self {
+            width: length,
+            height: 2,
+            tiles,
+            path: (0..length).rev().map(|x| (x, 0)).collect(),
+        }
+    }
+
+    /// Follow the path tiles from the spawn until the base is reached
+    fn trace_path(&mut self, base: (u32, u32)) -> io::Result<()> {
+        let mut current = self.path[0];
+        while current != base {
+            let next: Vec<(u32, u32)> = self.neighbors(current).into_iter()
+                .filter(|v| self.tile(*v) == Tile::Path && !self.path.contains(v))
+                .collect();
+            if next.len() != 1 {
+                return Err(invalid_map(format!(
+                    "The path must lead from the spawn to the base without branching (at {}, {})",
+                    current.0,
+                    current.1,
+                )));
+            }
+            current = next[0];
+            self.path.push(current);
+        }
+        Ok(())
+    }
+
+    /// Make sure a map that wasn't parsed from a map file (such as one read from a save) has a tile
+    /// for every coordinate and that its path is the single path from its spawn to its base
+    pub fn validate(&self) -> io::Result<()> {
+        if self.width == 0 || self.tiles.len() != self.width as usize * self.height as usize {
+            return Err(invalid_map("The map must have a tile for every coordinate"));
+        }
+        if self.path.iter().any(|v| v.0 >= self.width || v.1 >= self.height) {
+            return Err(invalid_map("The path must be on the map"));
+        }
+        let (Some(spawn), Some(base)) = (self.path.first(), self.path.last()) else {
+            return Err(invalid_map("The map must have an enemy spawn and a base"));
+        };
+        if self.tile(*spawn) != Tile::Path {
+            return Err(invalid_map("The enemy spawn must be on the path"));
+        }
+
+        let mut traced = Self {
+            width: self.width,
+            height: self.height,
+            tiles: self.tiles.clone(),
+            path: vec![*spawn],
+        };
+        traced.trace_path(*base)?;
+        if traced.path != self.path {
+            return Err(invalid_map("The path must be the path from the spawn to the base"));
+        }
+        Ok(())
+    }
+
+    /// Whether a tile is on the map
+    pub fn contains(&self, (x, y): (u32, u32)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// The tiles directly above, below, left, and right of a tile
+    fn neighbors(&self, (x, y): (u32, u32)) -> Vec<(u32, u32)> {
+        let mut neighbors = Vec::with_capacity(4);
+        if x > 0 {
+            neighbors.push((x - 1, y));
+        }
+        if y > 0 {
+            neighbors.push((x, y - 1));
+        }
+        if x + 1 < self.width {
+            neighbors.push((x + 1, y));
+        }
+        if y + 1 < self.height {
+            neighbors.push((x, y + 1));
+        }
+        neighbors
+    }
+
+    /// The tile at the given coordinates
+    pub fn tile(&self, (x, y): (u32, u32)) -> Tile {
+        self.tiles[(y * self.width + x) as usize]
+    }
+
+    /// The number of positions an enemy walks through to reach the base
+    pub fn path_length(&self) -> u32 {
+        self.path.len() as u32
+    }
+
+    /// The coordinates of an enemy with a given number of positions left to reach the base
+    /// (enemies that haven't entered the map yet wait at the spawn)
+    pub fn path_coordinates(&self, enemy_position: u32) -> (u32, u32) {
+        let index = self.path.len().saturating_sub(1 + enemy_position as usize);
+        self.path[index]
+    }
+
+    /// The coordinates of every buildable tile (row by row)
+    pub fn buildable_tiles(&self) -> Vec<(u32, u32)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|v| self.tile(*v) == Tile::Buildable)
+            .collect()
+    }
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::parse(DEFAULT_MAP).expect("Default Map must be valid")
+    }
+}
+
+/// Whether an enemy tile is within a tower's range (a radius in tiles)
+pub fn in_range(tower_tile: (u32, u32), range: u32, enemy_tile: (u32, u32)) -> bool {
+    let dx = tower_tile.0.abs_diff(enemy_tile.0);
+    let dy = tower_tile.1.abs_diff(enemy_tile.1);
+    dx * dx + dy * dy <= range * range
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_winding_path() {
+        let map = Map::parse("
+            S=.#
+            .=..
+            #==B
+        ".replace(' ', "").as_str()).unwrap();
+
+        assert_eq!((map.width, map.height), (4, 3));
+        assert_eq!(map.path, vec![(0, 0), (1, 0), (1, 1), (1, 2), (2, 2), (3, 2)]);
+        assert_eq!(map.buildable_tiles(), vec![(3, 0), (0, 2)]);
+        assert_eq!(map.tile((2, 0)), Tile::Empty);
+    }
+
+    #[test]
+    fn test_path_coordinates() {
+        let map = Map::parse("S==B\n####").unwrap();
+
+        assert_eq!(map.path_length(), 4);
+        assert_eq!(map.path_coordinates(0), (3, 0));
+        assert_eq!(map.path_coordinates(3), (0, 0));
+
+        // Enemies that haven't entered the map wait at the spawn
+        assert_eq!(map.path_coordinates(10), (0, 0));
+    }
+
+    #[test]
+    fn test_straight_map() {
+        let map = Map::straight(10);
+
+        assert_eq!(map.path_length(), 10);
+        assert_eq!(map.path_coordinates(3), (3, 0));
+        assert_eq!(map.tile((3, 1)), Tile::Buildable);
+        assert_eq!(map.buildable_tiles().len(), 10);
+    }
+
+    #[test]
+    fn test_parse_map_with_branching_path() {
+        let map = Map::parse("S==B\n.=..\n.=..");
+
+        assert_eq!(map.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_map_without_base() {
+        let map = Map::parse("S===\n####");
+
+        assert_eq!(map.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_map_with_uneven_rows() {
+        let map = Map::parse("S==B\n###");
+
+        assert_eq!(map.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_validate_map() {
+        assert!(Map::default().validate().is_ok());
+        assert!(Map::straight(10).validate().is_ok());
+
+        // A path that skips a tile of the traced path
+        let mut map = Map::straight(10);
+        map.path.remove(3);
+        assert_eq!(map.validate().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // Missing tiles
+        let mut map = Map::straight(10);
+        map.tiles.pop();
+        assert_eq!(map.validate().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_in_range() {
+        assert!(in_range((5, 5), 2, (5, 3)));
+        assert!(in_range((5, 5), 2, (4, 4)));
+        assert!(!in_range((5, 5), 2, (3, 3)));
+        assert!(!in_range((5, 5), 0, (5, 6)));
+    }
+}
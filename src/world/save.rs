@@ -0,0 +1,428 @@
+//!
+//! Saving and Loading Games
+//! 
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{EnemyType, GamePhase, HealthChange, Layer, Projectile, StatusEffect, TemporaryShield, TowerDefenseWorld, TowerTarget, TowerType};
+use crate::catalog::{enemy_catalog::EnemyCatalog, tower_catalog::TowerCatalog, wave_catalog::WaveCatalog};
+use crate::rng::Rng;
+use crate::world::map::Map;
+
+/// Create an invalid save file error
+fn invalid_save(err: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// The version of the save file format written by this version of the game
+pub const SAVE_VERSION: u32 = 1;
+
+/// A snapshot of a running game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveFile {
+    // The version of the save file format
+    pub version: u32,
+
+    // The components of every entity (indexed by entity id)
+    pub tower_type: Vec<Option<TowerType>>,
+    pub enemy_type: Vec<Option<EnemyType>>,
+    pub sprite: Vec<Option<String>>,
+    pub health: Vec<Option<u32>>,
+    pub health_change: Vec<Option<HealthChange>>,
+    pub enemy_position: Vec<Option<u32>>,
+    pub enemy_tile: Vec<Option<(u32, u32)>>,
+    pub enemy_layer: Vec<Option<Layer>>,
+    pub speed: Vec<Option<u32>>,
+    pub move_progress: Vec<Option<u32>>,
+    pub status_effects: Vec<Option<Vec<StatusEffect>>>,
+    pub shield: Vec<Option<u32>>,
+    pub boss_phase: Vec<Option<u32>>,
+    pub temporary_shield: Vec<Option<TemporaryShield>>,
+    pub support_cooldown: Vec<Option<u32>>,
+    pub detected: Vec<Option<bool>>,
+    pub target_enemy: Vec<Option<TowerTarget>>,
+    pub tower_tile: Vec<Option<(u32, u32)>>,
+    pub attack_cooldown: Vec<Option<u32>>,
+    pub invested_points: Vec<Option<u32>>,
+    pub projectile: Vec<Option<Projectile>>,
+
+    // The state of the game
+    pub base_health: u32,
+    pub wave_start_base_health: u32,
+    pub points: u32,
+    pub level: u32,
+    pub cursor: (u32, u32),
+    pub max_towers: u32,
+    pub refund_percent: u32,
+    pub rng: Rng,
+    pub game_phase: GamePhase,
+    pub enemies_killed: u32,
+    pub overkill_damage: u32,
+
+    // The catalogs and map the game was played with
+    pub tower_catalog: TowerCatalog,
+    pub enemy_catalog: EnemyCatalog,
+    pub wave_catalog: WaveCatalog,
+    pub map: Map,
+}
+
+impl SaveFile {
+    /// Parse a save file from the contents of a file
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let save: Self = ron::from_str(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        if save.version != SAVE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported save file version {} (expected {})", save.version, SAVE_VERSION),
+            ));
+        }
+
+        let entities = save.tower_type.len();
+        if [
+            save.enemy_type.len(),
+            save.sprite.len(),
+            save.health.len(),
+            save.health_change.len(),
+            save.enemy_position.len(),
+            save.enemy_tile.len(),
+            save.enemy_layer.len(),
+            save.speed.len(),
+            save.move_progress.len(),
+            save.status_effects.len(),
+            save.shield.len(),
+            save.boss_phase.len(),
+            save.temporary_shield.len(),
+            save.support_cooldown.len(),
+            save.detected.len(),
+            save.target_enemy.len(),
+            save.tower_tile.len(),
+            save.attack_cooldown.len(),
+            save.invested_points.len(),
+            save.projectile.len(),
+        ].iter().any(|v| *v != entities) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Every component in the save file must have an entry for every entity",
+            ));
+        }
+
+        // The catalogs and map are read straight from the save rather than parsed from their files
+        save.tower_catalog.validate()?;
+        save.enemy_catalog.validate()?;
+        save.wave_catalog.validate(&save.enemy_catalog)?;
+        save.map.validate()?;
+        save.validate_entities()?;
+
+        Ok(save)
+    }
+
+    /// Make sure every entity only names types in the catalogs, is on the map, and only refers to
+    /// entities in the save
+    fn validate_entities(&self) -> io::Result<()> {
+        let entities = self.tower_type.len();
+        let unknown_tower_type = |tower_type: &TowerType| !self.tower_catalog.towers.contains_key(tower_type);
+        if self.tower_type.iter().flatten().any(unknown_tower_type)
+            || self.projectile.iter().flatten().any(|v| unknown_tower_type(&v.tower_type))
+        {
+            return Err(invalid_save("Every tower in the save file must be in its tower catalog"));
+        }
+        if self.enemy_type.iter().flatten().any(|v| !self.enemy_catalog.contains(v)) {
+            return Err(invalid_save("Every enemy in the save file must be in its enemy catalog"));
+        }
+        if self.projectile.iter().flatten().any(|v| v.target.is_some_and(|v| v >= entities)) {
+            return Err(invalid_save("Every shot in the save file must target an entity in the save file"));
+        }
+        let tiles = self.tower_tile.iter().chain(self.enemy_tile.iter()).flatten()
+            .chain(self.projectile.iter().flatten().flat_map(|v| [&v.origin, &v.target_tile]));
+        if !self.map.contains(self.cursor) || tiles.copied().any(|v| !self.map.contains(v)) {
+            return Err(invalid_save("Every tile in the save file must be on its map"));
+        }
+        Ok(())
+    }
+
+    /// Convert the save file into the contents of a file
+    pub fn to_contents(&self) -> io::Result<String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+}
+
+impl TowerDefenseWorld {
+    /// Take a snapshot of the running game
+    pub fn to_save_file(&self) -> SaveFile {
+        SaveFile {
+            version: SAVE_VERSION,
+            tower_type: self.tower_type.read().unwrap().clone(),
+            enemy_type: self.enemy_type.read().unwrap().clone(),
+            sprite: self.sprite.read().unwrap().clone(),
+            health: self.health.read().unwrap().clone(),
+            health_change: self.health_change.read().unwrap().clone(),
+            enemy_position: self.enemy_position.read().unwrap().clone(),
+            enemy_tile: self.enemy_tile.read().unwrap().clone(),
+            enemy_layer: self.enemy_layer.read().unwrap().clone(),
+            speed: self.speed.read().unwrap().clone(),
+            move_progress: self.move_progress.read().unwrap().clone(),
+            status_effects: self.status_effects.read().unwrap().clone(),
+            shield: self.shield.read().unwrap().clone(),
+            boss_phase: self.boss_phase.read().unwrap().clone(),
+            temporary_shield: self.temporary_shield.read().unwrap().clone(),
+            support_cooldown: self.support_cooldown.read().unwrap().clone(),
+            detected: self.detected.read().unwrap().clone(),
+            target_enemy: self.target_enemy.read().unwrap().clone(),
+            tower_tile: self.tower_tile.read().unwrap().clone(),
+            attack_cooldown: self.attack_cooldown.read().unwrap().clone(),
+            invested_points: self.invested_points.read().unwrap().clone(),
+            projectile: self.projectile.read().unwrap().clone(),
+            base_health: self.base_health.read().unwrap().expect("Base Health must be initialized"),
+            wave_start_base_health: self.wave_start_base_health.read().unwrap()
+                .expect("Wave Start Base Health must be initialized"),
+            points: self.points.read().unwrap().expect("Points must be initialized"),
+            level: self.level.read().unwrap().expect("Level must be initialized"),
+            cursor: self.cursor.read().unwrap().expect("Cursor must be initialized"),
+            max_towers: self.max_towers.read().unwrap().expect("Max Towers must be initialized"),
+            refund_percent: self.refund_percent.read().unwrap().expect("Refund Percent must be initialized"),
+            rng: self.rng.read().unwrap().expect("Rng must be initialized"),
+            game_phase: self.game_phase.read().unwrap().expect("Game Phase must be initialized"),
+            enemies_killed: self.enemies_killed.read().unwrap().expect("Enemies Killed must be initialized"),
+            overkill_damage: self.overkill_damage.read().unwrap().expect("Overkill Damage must be initialized"),
+            tower_catalog: self.tower_catalog.read().unwrap().clone().expect("Tower Catalog must be loaded"),
+            enemy_catalog: self.enemy_catalog.read().unwrap().clone().expect("Enemy Catalog must be loaded"),
+            wave_catalog: self.wave_catalog.read().unwrap().clone().expect("Wave Catalog must be loaded"),
+            map: self.map.read().unwrap().clone().expect("Map must be loaded"),
+        }
+    }
+
+    /// Replace every entity and the state of the game with a snapshot
+    pub fn load_save_file(&mut self, save: SaveFile) {
+        self.remove_all_entities();
+
+        // Restore the singular components
+        self.set_tower_catalog(save.tower_catalog);
+        self.set_enemy_catalog(save.enemy_catalog);
+        self.set_wave_catalog(save.wave_catalog);
+        self.set_map(save.map);
+        self.initialize_singular_components(save.base_health);
+        self.set_wave_start_base_health(save.wave_start_base_health);
+        self.set_points(save.points);
+        self.set_level(save.level);
+        self.set_cursor(save.cursor);
+        self.set_max_towers(save.max_towers);
+        // Selling a tower must never pay out more than was invested in it
+        self.set_refund_percent(save.refund_percent.min(100));
+        self.set_rng(save.rng);
+        self.set_game_phase(save.game_phase);
+        self.set_enemies_killed(save.enemies_killed);
+        self.set_overkill_damage(save.overkill_damage);
+
+        // Restore the entities
+        self.add_entities(save.tower_type.len());
+        *self.tower_type.write().unwrap() = save.tower_type;
+        *self.enemy_type.write().unwrap() = save.enemy_type;
+        *self.sprite.write().unwrap() = save.sprite;
+        *self.health.write().unwrap() = save.health;
+        *self.health_change.write().unwrap() = save.health_change;
+        *self.enemy_position.write().unwrap() = save.enemy_position;
+        *self.enemy_tile.write().unwrap() = save.enemy_tile;
+        *self.enemy_layer.write().unwrap() = save.enemy_layer;
+        *self.speed.write().unwrap() = save.speed;
+        *self.move_progress.write().unwrap() = save.move_progress;
+        *self.status_effects.write().unwrap() = save.status_effects;
+        *self.shield.write().unwrap() = save.shield;
+        *self.boss_phase.write().unwrap() = save.boss_phase;
+        *self.temporary_shield.write().unwrap() = save.temporary_shield;
+        *self.support_cooldown.write().unwrap() = save.support_cooldown;
+        *self.detected.write().unwrap() = save.detected;
+        *self.target_enemy.write().unwrap() = save.target_enemy;
+        *self.tower_tile.write().unwrap() = save.tower_tile;
+        *self.attack_cooldown.write().unwrap() = save.attack_cooldown;
+        *self.invested_points.write().unwrap() = save.invested_points;
+        *self.projectile.write().unwrap() = save.projectile;
+    }
+
+    /// Save the running game to a file
+    pub fn save_game<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_save_file().to_contents()?)
+    }
+
+    /// Load a saved game from a file
+    pub fn load_game<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let save = SaveFile::parse(&fs::read_to_string(path)?)?;
+        self.load_save_file(save);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_game() {
+        let world = TowerDefenseWorld::new();
+        let save_path = std::env::temp_dir().join(format!("td-tui-test-save-{}.ron", std::process::id()));
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Start a game and make some progress
+            world.new_game(100);
+            world.set_points(42);
+            world.set_level(3);
+            world.set_base_health(64);
+            world.set_cursor((2, 4));
+            world.set_health(10, 0);
+
+            world.save_game(&save_path).unwrap();
+        }
+
+        let loaded_world = TowerDefenseWorld::new();
+        loaded_world.write().unwrap().load_game(&save_path).unwrap();
+        std::fs::remove_file(&save_path).unwrap();
+
+        // Check that the loaded world matches the saved world
+        let read_world = world.read().unwrap();
+        let loaded_world = loaded_world.read().unwrap();
+        assert_eq!(loaded_world.to_save_file(), read_world.to_save_file());
+        assert_eq!(*loaded_world.points.read().unwrap(), Some(42));
+        assert_eq!(*loaded_world.level.read().unwrap(), Some(3));
+        assert_eq!(*loaded_world.base_health.read().unwrap(), Some(64));
+        assert_eq!(loaded_world.health.read().unwrap()[10], Some(0));
+    }
+
+    #[test]
+    fn test_load_game_replaces_existing_entities() {
+        let world = TowerDefenseWorld::new();
+
+        let save = {
+            let mut world = world.write().unwrap();
+            world.new_game(100);
+            world.to_save_file()
+        };
+
+        {
+            let mut world = world.write().unwrap();
+
+            // Spawn more enemies and then load the save
+            world.spawn_wave(2);
+            world.load_save_file(save.clone());
+        }
+
+        let read_world = world.read().unwrap();
+        assert_eq!(read_world.to_save_file(), save);
+    }
+
+    #[test]
+    fn test_parse_save_file_with_unknown_wave_enemy() {
+        let world = TowerDefenseWorld::new();
+
+        let mut save = {
+            let mut world = world.write().unwrap();
+            world.new_game(100);
+            world.to_save_file()
+        };
+        save.wave_catalog.endless.enemies.push(EnemyType::from("missing"));
+
+        let parsed = SaveFile::parse(&save.to_contents().unwrap());
+        assert_eq!(parsed.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_save_file_with_invalid_tower_catalog() {
+        let world = TowerDefenseWorld::new();
+
+        let mut save = {
+            let mut world = world.write().unwrap();
+            world.new_game(100);
+            world.to_save_file()
+        };
+        save.tower_catalog.towers.get_mut(&TowerType::from("base")).unwrap().upgrade = Some(TowerType::broken());
+
+        let parsed = SaveFile::parse(&save.to_contents().unwrap());
+        assert_eq!(parsed.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_save_file_with_broken_map_path() {
+        let world = TowerDefenseWorld::new();
+
+        let mut save = {
+            let mut world = world.write().unwrap();
+            world.new_game(100);
+            world.to_save_file()
+        };
+        save.map.path.remove(1);
+
+        let parsed = SaveFile::parse(&save.to_contents().unwrap());
+        assert_eq!(parsed.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_save_file_with_invalid_entities() {
+        let world = TowerDefenseWorld::new();
+
+        let save = {
+            let mut world = world.write().unwrap();
+            world.new_game(100);
+            world.to_save_file()
+        };
+        let enemy_id = save.enemy_type.iter().position(|v| v.is_some()).unwrap();
+
+        // An enemy type missing from the enemy catalog
+        let mut unknown_enemy = save.clone();
+        unknown_enemy.enemy_type[enemy_id] = Some(EnemyType::from("missing"));
+        assert!(SaveFile::parse(&unknown_enemy.to_contents().unwrap()).is_err());
+
+        // A tower off the map
+        let mut tower_off_map = save.clone();
+        tower_off_map.tower_tile[0] = Some((1000, 1000));
+        assert!(SaveFile::parse(&tower_off_map.to_contents().unwrap()).is_err());
+
+        // A shot at an entity that isn't in the save
+        let mut missing_target = save.clone();
+        missing_target.projectile[enemy_id] = Some(Projectile {
+            tower_type: TowerType::from("base"),
+            target: Some(save.tower_type.len()),
+            origin: (0, 0),
+            target_tile: (0, 0),
+            travelled: 0,
+        });
+        assert!(SaveFile::parse(&missing_target.to_contents().unwrap()).is_err());
+
+        assert!(SaveFile::parse(&save.to_contents().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_load_save_file_caps_refund_percent() {
+        let world = TowerDefenseWorld::new();
+
+        let mut save = {
+            let mut world = world.write().unwrap();
+            world.new_game(100);
+            world.to_save_file()
+        };
+        save.refund_percent = 200;
+        world.write().unwrap().load_save_file(save);
+
+        assert_eq!(*world.read().unwrap().refund_percent.read().unwrap(), Some(100));
+    }
+
+    #[test]
+    fn test_parse_save_file_with_unsupported_version() {
+        let world = TowerDefenseWorld::new();
+
+        let mut save = {
+            let mut world = world.write().unwrap();
+            world.new_game(100);
+            world.to_save_file()
+        };
+        save.version = SAVE_VERSION + 1;
+
+        let parsed = SaveFile::parse(&save.to_contents().unwrap());
+        assert_eq!(parsed.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -4,61 +4,151 @@
 
 use nate_engine::world;
 
-use crate::{EnemyType, TowerTarget, TowerType, TOWER_SEPARATION};
-
-/// World the running tower defense games
-#[world(
-    singular = [
-        base_health,
-        base_damage,
-        removal_entities,
-        alive_enemies,
-        selected_tower,
-        help_displayed,
-        points,
-        level,
-        upgrading_tower,
-        downgrading_tower,
-    ]
-)]
-pub struct TowerDefenseWorld {
-    // The tower type for each tower
-    tower_type: TowerType,
-    // The enemy type for each enemy
-    enemy_type: EnemyType,
-    // The sprite for a displayable entity (string)
-    sprite: String,
-    // The health that an enemy has left
-    health: u32,
-    // The damage being done to an entity
-    health_change: i32,
-    // The position of the enemy [0, TOTAL_POSITIONS)
-    enemy_position: u32,
-    // The enemy index for a tower to target
-    target_enemy: TowerTarget,
-    // the left and right most position the tower can attack
-    tower_bounds: (u32, u32),
-
-    // The current health of the base
-    base_health: u32,
-    // The current damage to deal to the base
-    base_damage: i32,
-    // Entities to remove at the next remove step
-    removal_entities: Vec<usize>,
-    // Total Alive Enemies
-    alive_enemies: u32,
-    // Tower the cursor is on
-    selected_tower: u32,
-    // Whether or not help is being displayed
-    help_displayed: bool,
-    // Points obtained
-    points: u32,
-    // Current Level
-    level: u32,
-    // Upgrading the current tower (flag passed by the input handler)
-    upgrading_tower: bool,
-    // Downgrading the current tower (flag passed by the input handler)
-    downgrading_tower: bool,
+use crate::{EnemyType, GamePhase, HealthChange, Layer, Projectile, StatusEffect, TemporaryShield, TowerTarget, TowerType, WaveIncome, DEFAULT_MAX_TOWERS, DEFAULT_REFUND_PERCENT, DEFAULT_SEED};
+use crate::rng::Rng;
+use crate::catalog::{enemy_catalog::EnemyCatalog, tower_catalog::TowerCatalog, wave_catalog::WaveCatalog};
+use crate::world::map::{Map, Tile};
+
+pub use components::TowerDefenseWorld;
+
+// the world macro generates setters that take `&Vec<usize>` (so the lint is only allowed for the
+// generated world)
+#[allow(clippy::ptr_arg)]
+mod components {
+    use super::*;
+
+    /// World the running tower defense games
+    #[world(
+        singular = [
+            base_health,
+            wave_start_base_health,
+            base_damage,
+            removal_entities,
+            fired_projectiles,
+            alive_enemies,
+            cursor,
+            max_towers,
+            refund_percent,
+            rng,
+            help_displayed,
+            points,
+            level,
+            upgrading_tower,
+            branching_tower,
+            downgrading_tower,
+            demolishing_tower,
+            game_phase,
+            enemies_killed,
+            overkill_damage,
+            restarting_game,
+            tower_catalog,
+            enemy_catalog,
+            wave_catalog,
+            map,
+        ]
+    )]
+    pub struct TowerDefenseWorld {
+        // The tower type for each tower
+        tower_type: TowerType,
+        // The enemy type for each enemy
+        enemy_type: EnemyType,
+        // The sprite for a displayable entity (string)
+        sprite: String,
+        // The health that an enemy has left
+        health: u32,
+        // The damage and healing an entity takes when health changes are next resolved
+        health_change: HealthChange,
+        // The number of path tiles an enemy has left to walk to reach the base
+        enemy_position: u32,
+        // The map tile an enemy is on
+        enemy_tile: (u32, u32),
+        // The layer an enemy moves in (walking along the path or flying above it)
+        enemy_layer: Layer,
+        // The number of positions an enemy moves each second
+        speed: u32,
+        // The progress (in thousandths of a position) an enemy has made towards its next position
+        move_progress: u32,
+        // The status effects an enemy is affected by
+        status_effects: Vec<StatusEffect>,
+        // The damage an enemy's shield absorbs before the enemy loses health
+        shield: u32,
+        // The number of boss phases an enemy has entered
+        boss_phase: u32,
+        // The shield granted to an enemy by nearby shield-bearers
+        temporary_shield: TemporaryShield,
+        // The time (in milliseconds) until a support enemy next uses its support ability
+        support_cooldown: u32,
+        // Whether an enemy can be targeted by towers (stealth enemies must be within range of a detector)
+        detected: bool,
+        // The enemy index for a tower to target
+        target_enemy: TowerTarget,
+        // The map tile a tower is built on
+        tower_tile: (u32, u32),
+        // The time (in milliseconds) until a tower can attack again
+        attack_cooldown: u32,
+        // The points spent building and upgrading a tower
+        invested_points: u32,
+        // A shot flying from a tower to an enemy
+        projectile: Projectile,
+
+        // The current health of the base
+        base_health: u32,
+        // The health of the base when the current wave was spawned
+        wave_start_base_health: u32,
+        // The current damage to deal to the base
+        base_damage: i32,
+        // Entities to remove at the next remove step
+        removal_entities: Vec<usize>,
+        // Shots fired by towers this tick that haven't been added to the world yet
+        fired_projectiles: Vec<Projectile>,
+        // Total Alive Enemies
+        alive_enemies: u32,
+        // The map tile the placement cursor is on
+        cursor: (u32, u32),
+        // The most towers that can be built at once
+        max_towers: u32,
+        // The percentage of the points invested in a tower refunded when it is sold
+        refund_percent: u32,
+        // The random number generator for critical hits and dodges
+        rng: Rng,
+        // Whether or not help is being displayed
+        help_displayed: bool,
+        // Points obtained
+        points: u32,
+        // Current Level
+        level: u32,
+        // Upgrading the current tower (flag passed by the input handler)
+        upgrading_tower: bool,
+        // The branch to upgrade the current tower into (passed by the input handler)
+        branching_tower: Option<usize>,
+        // Downgrading the current tower (flag passed by the input handler)
+        downgrading_tower: bool,
+        // Demolishing the current tower back into a broken tower (flag passed by the input handler)
+        demolishing_tower: bool,
+        // Current phase of the game
+        game_phase: GamePhase,
+        // Total enemies killed by towers
+        enemies_killed: u32,
+        // Total damage dealt to enemies beyond what it took to kill them
+        overkill_damage: u32,
+        // Restarting the game (flag passed by the input handler)
+        restarting_game: bool,
+        // The definitions of every type of tower
+        tower_catalog: TowerCatalog,
+        // The definitions of every type of enemy
+        enemy_catalog: EnemyCatalog,
+        // The waves of enemies spawned each level
+        wave_catalog: WaveCatalog,
+        // The map enemies walk along and towers are built on
+        map: Map,
+    }
+
+    impl TowerDefenseWorld {
+        /// The number of entities in the world
+        pub fn total_entities(&self) -> usize {
+            self.entities.read().unwrap().len()
+        }
+    }
 }
 
 impl TowerDefenseWorld {
@@ -70,27 +160,41 @@ impl TowerDefenseWorld {
         position: u32,
         health: u32
     ) -> usize {
+        let (speed, layer, stealth) = {
+            let catalog = self.enemy_catalog.read().unwrap();
+            let definition = catalog.as_ref().expect("Enemy Catalog must be loaded").get(&enemy_type);
+            (definition.speed, definition.layer, definition.stealth)
+        };
+        let tile = self.map.read().unwrap().as_ref()
+            .expect("Map must be loaded")
+            .path_coordinates(position);
         let enemy_id = self.add_entity();
         self.set_enemy_type(enemy_id, enemy_type);
         self.set_sprite(enemy_id, sprite);
         self.set_enemy_position(enemy_id, position);
+        self.set_enemy_tile(enemy_id, tile);
+        self.set_enemy_layer(enemy_id, layer);
         self.set_health(enemy_id, health);
+        self.set_health_change(enemy_id, HealthChange::default());
+        self.set_speed(enemy_id, speed);
+        self.set_move_progress(enemy_id, 0);
+        self.set_status_effects(enemy_id, Vec::new());
+        self.set_shield(enemy_id, 0);
+        self.set_boss_phase(enemy_id, 0);
+        self.set_temporary_shield(enemy_id, TemporaryShield::default());
+        self.set_support_cooldown(enemy_id, 0);
+        self.set_detected(enemy_id, !stealth);
         enemy_id
     }
 
-    /// Add a singular base enemy
-    pub fn add_base_enemy(&mut self, position: u32) -> usize {
-        self.add_enemy(EnemyType::Base, String::from("X"), position, 1)
-    }
-
-    /// Add a singular second enemy
-    pub fn add_second_enemy(&mut self, position: u32) -> usize {
-        self.add_enemy(
-            EnemyType::Second,
-            String::from("Q"),
-            position,
-            2,
-        )
+    /// Add a singular enemy with the sprite and health from the enemy catalog
+    pub fn add_catalog_enemy(&mut self, enemy_type: EnemyType, position: u32) -> usize {
+        let (sprite, health) = {
+            let catalog = self.enemy_catalog.read().unwrap();
+            let definition = catalog.as_ref().expect("Enemy Catalog must be loaded").get(&enemy_type);
+            (definition.sprite.clone(), definition.health)
+        };
+        self.add_enemy(enemy_type, sprite, position, health)
     }
 
     /// Add a tower entity
@@ -98,13 +202,15 @@ impl TowerDefenseWorld {
         &mut self,
         tower_type: TowerType,
         target_enemy: TowerTarget,
-        tower_bounds: (u32, u32),
+        tower_tile: (u32, u32),
         sprite: String,
     ) -> usize {
         let tower_id = self.add_entity();
         self.set_tower_type(tower_id, tower_type);
         self.set_target_enemy(tower_id, target_enemy);
-        self.set_tower_bounds(tower_id, tower_bounds);
+        self.set_tower_tile(tower_id, tower_tile);
+        self.set_attack_cooldown(tower_id, 0);
+        self.set_invested_points(tower_id, 0);
         self.set_sprite(tower_id, sprite);
         tower_id
     }
@@ -112,194 +218,151 @@ impl TowerDefenseWorld {
     /// Add a broken tower
     pub fn add_broken_tower(
         &mut self,
+        tower_tile: (u32, u32),
     ) -> usize {
-        self.add_tower(
-            TowerType::Broken,
-            TowerTarget::First,
-            (0, 0),
-            String::from("-"),
-        )
+        self.add_catalog_tower(TowerType::broken(), TowerTarget::First, tower_tile)
     }
 
-    /// Add a base tower (range of 5 units)
-    pub fn add_base_tower(
+    /// Add a tower with the sprite from the tower catalog (as if every upgrade to reach it was paid for)
+    pub fn add_catalog_tower(
         &mut self,
+        tower_type: TowerType,
         target_enemy: TowerTarget,
-        tower_number: u32,
+        tower_tile: (u32, u32),
     ) -> usize {
-        let start = TOWER_SEPARATION * tower_number;
-        let end = TOWER_SEPARATION * (tower_number + 1);
-        let midpoint = (start + end) / 2;
-        self.add_tower(
-            TowerType::Base,
-            target_enemy,
-            (midpoint - 2, midpoint + 2),
-            String::from("!"),
-        )
+        let (sprite, total_cost) = {
+            let catalog = self.tower_catalog.read().unwrap();
+            let catalog = catalog.as_ref().expect("Tower Catalog must be loaded");
+            (catalog.get(&tower_type).sprite.clone(), catalog.total_cost(&tower_type))
+        };
+        let tower_id = self.add_tower(tower_type, target_enemy, tower_tile, sprite);
+        self.set_invested_points(tower_id, total_cost);
+        tower_id
     }
 
-    /// Add a second tower (range of 9 units)
-    pub fn add_second_tower(
-        &mut self,
-        target_enemy: TowerTarget,
-        tower_number: u32,
-    ) -> usize {
-        let midpoint = TOWER_SEPARATION / 2 * (2 * tower_number + 1);
-        self.add_tower(
-            TowerType::Second,
-            target_enemy,
-            (midpoint - 4, midpoint + 4),
-            String::from("%"),
-        )
+    /// Change the type of a tower, updating its sprite from the tower catalog
+    pub fn change_tower_type(
+        &self,
+        entity_id: usize,
+        tower_type: TowerType,
+    ) {
+        let sprite = self.tower_catalog.read().unwrap().as_ref()
+            .expect("Tower Catalog must be loaded")
+            .get(&tower_type)
+            .sprite
+            .clone();
+        *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(tower_type);
+        *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id must be valid") = Some(sprite);
     }
 
-    /// Add a third tower (range of 9 units)
-    pub fn add_third_tower(
-        &mut self,
-        target_enemy: TowerTarget,
-        tower_number: u32,
-    ) -> usize {
-        let midpoint = TOWER_SEPARATION / 2 * (2 * tower_number + 1);
-        self.add_tower(
-            TowerType::Third,
-            target_enemy,
-            (midpoint - 4, midpoint + 4),
-            String::from("$"),
-        )
+    /// Upgrade a given tower
+    pub fn upgrade_tower(
+        &self,
+        entity_id: usize,
+        current_tower_type: &TowerType,
+    ) {
+        let upgrade = self.tower_catalog.read().unwrap().as_ref()
+            .expect("Tower Catalog must be loaded")
+            .upgrade(current_tower_type)
+            .cloned();
+        if let Some(upgrade) = upgrade {
+            self.change_tower_type(entity_id, upgrade);
+        }
     }
 
-    /// Add a fourth tower (range of 11 units)
-    pub fn add_fourth_tower(
-        &mut self,
-        target_enemy: TowerTarget,
-        tower_number: u32,
-    ) -> usize {
-        let midpoint = TOWER_SEPARATION / 2 * (2 * tower_number + 1);
-        self.add_tower(
-            TowerType::Fourth,
-            target_enemy,
-            (midpoint - 5, midpoint + 5),
-            String::from("@"),
-        )
+    /// Upgrade a given tower into the first tower of one of its branch upgrade lines
+    pub fn branch_tower(
+        &self,
+        entity_id: usize,
+        current_tower_type: &TowerType,
+        branch: usize,
+    ) {
+        let branch = self.tower_catalog.read().unwrap().as_ref()
+            .expect("Tower Catalog must be loaded")
+            .branch(current_tower_type, branch)
+            .cloned();
+        if let Some(branch) = branch {
+            self.change_tower_type(entity_id, branch);
+        }
     }
 
-    /// Add a fifth tower (range of 11 units)
-    pub fn add_fifth_tower(
-        &mut self,
-        target_enemy: TowerTarget,
-        tower_number: u32,
-    ) -> usize {
-        let midpoint = TOWER_SEPARATION / 2 * (2 * tower_number + 1);
-        self.add_tower(
-            TowerType::Fifth,
-            target_enemy,
-            (midpoint - 5, midpoint + 5),
-            String::from("^"),
-        )
+    /// Record points spent upgrading a given tower
+    pub fn invest_points(&self, entity_id: usize, points: u32) {
+        if let Some(invested_points) = self.invested_points.write().unwrap()[entity_id].as_mut() {
+            *invested_points += points;
+        }
     }
 
-    /// Add a final tower (range of 15 units)
-    pub fn add_final_tower(
-        &mut self,
-        target_enemy: TowerTarget,
-        tower_number: u32,
-    ) -> usize {
-        let midpoint = TOWER_SEPARATION / 2 * (2 * tower_number + 1);
-        self.add_tower(
-            TowerType::Final,
-            target_enemy,
-            (midpoint - 7, midpoint + 7),
-            String::from("|"),
-        )
+    /// The points refunded for selling off some of the points invested in a tower
+    fn refund(&self, points: u32) -> u32 {
+        points * self.refund_percent.read().unwrap().expect("Refund Percent must be initialized") / 100
     }
 
-    /// Upgrade a given tower
-    pub fn upgrade_tower(
+    /// Sell a given tower back into the tower it was upgraded from, returning the points refunded
+    pub fn sell_tower(
         &self,
-        tower_number: u32,
         entity_id: usize,
-        current_tower_type: TowerType,
-    ) {
-        let midpoint = TOWER_SEPARATION / 2 * (2 * tower_number + 1);
-        match current_tower_type {
-            TowerType::Final => {
-
-            },
-            TowerType::Fifth => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(TowerType::Final);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must be Valid") = Some((midpoint - 7, midpoint + 7));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id must be valid") = Some(String::from("|"));
-            },
-            TowerType::Fourth => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(TowerType::Fifth);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must be Valid") = Some((midpoint - 5, midpoint + 5));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id must be valid") = Some(String::from("^"));
-            },
-            TowerType::Third => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(TowerType::Fourth);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must be Valid") = Some((midpoint - 5, midpoint + 5));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id must be valid") = Some(String::from("@"));
-            },
-            TowerType::Second => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(TowerType::Third);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must be Valid") = Some((midpoint - 4, midpoint + 4));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id must be valid") = Some(String::from("$"));
-            },
-            TowerType::Base => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(TowerType::Second);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must be Valid") = Some((midpoint - 4, midpoint + 4));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id must be valid") = Some(String::from("%"));
-            },
-            TowerType::Broken => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(TowerType::Base);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must be Valid") = Some((midpoint - 2, midpoint + 2));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id must be valid") = Some(String::from("!"));
-            },
-        }
+        current_tower_type: &TowerType,
+    ) -> u32 {
+        let (downgrade, cost) = {
+            let catalog = self.tower_catalog.read().unwrap();
+            let catalog = catalog.as_ref().expect("Tower Catalog must be loaded");
+            (catalog.downgrade(current_tower_type).cloned(), catalog.get(current_tower_type).cost)
+        };
+        let Some(downgrade) = downgrade else {
+            return 0;
+        };
+        self.change_tower_type(entity_id, downgrade);
+
+        // Only the points spent on the tower's last upgrade are sold off
+        let sold_points = {
+            let mut invested_points = self.invested_points.write().unwrap();
+            let invested_points = invested_points[entity_id].get_or_insert(0);
+            let sold_points = cost.min(*invested_points);
+            *invested_points -= sold_points;
+            sold_points
+        };
+        self.refund(sold_points)
     }
 
-    /// Sell a given tower
-    pub fn sell_tower(
-        &self,
-        tower_number: u32,
-        entity_id: usize,
-        current_tower_type: TowerType,
-    ) {
-        let midpoint = TOWER_SEPARATION / 2 * (2 * tower_number + 1);
-        match current_tower_type {
-            TowerType::Broken => {
-
-            },
-            TowerType::Base => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some(TowerType::Broken);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some((0, 0));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(String::from("-"));
-            },
-            TowerType::Second => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some(TowerType::Base);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some((midpoint - 2, midpoint + 2));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(String::from("!"));
-            },
-            TowerType::Third => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some(TowerType::Second);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some((midpoint - 4, midpoint + 4));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(String::from("%"));
-            },
-            TowerType::Fourth => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some(TowerType::Third);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some((midpoint - 4, midpoint + 4));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(String::from("$"));
-            },
-            TowerType::Fifth => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some(TowerType::Fourth);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some((midpoint - 5, midpoint + 5));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(String::from("@"));
-            },
-            TowerType::Final => {
-                *self.tower_type.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some(TowerType::Fifth);
-                *self.tower_bounds.write().unwrap().get_mut(entity_id).expect("Entity ID Must Be Valid") = Some((midpoint - 5, midpoint + 5));
-                *self.sprite.write().unwrap().get_mut(entity_id).expect("Entity Id Must Be Valid") = Some(String::from("^"));
-            }
+    /// Demolish a given tower straight back into a broken tower, returning the points refunded
+    pub fn demolish_tower(&self, entity_id: usize) -> u32 {
+        self.change_tower_type(entity_id, TowerType::broken());
+        let sold_points = self.invested_points.write().unwrap()[entity_id].replace(0).unwrap_or(0);
+        self.refund(sold_points)
+    }
+
+    /// The entity id of the tower built on a tile
+    pub fn tower_at(&self, tile: (u32, u32)) -> Option<usize> {
+        self.tower_tile.read().unwrap().iter().position(|v| *v == Some(tile))
+    }
+
+    /// The entity id of the tower under the placement cursor
+    pub fn selected_tower(&self) -> Option<usize> {
+        self.tower_at(self.cursor.read().unwrap().expect("Cursor must be initialized"))
+    }
+
+    /// The number of towers that have been built
+    pub fn total_towers(&self) -> u32 {
+        self.tower_type.read().unwrap().iter().filter(|v| v.is_some()).count() as u32
+    }
+
+    /// Whether a new tower can be built on a tile (a free buildable tile while there
+    /// are fewer than the maximum number of towers)
+    pub fn can_build_tower(&self, tile: (u32, u32)) -> bool {
+        let buildable = {
+            let map = self.map.read().unwrap();
+            let map = map.as_ref().expect("Map must be loaded");
+            map.contains(tile) && map.tile(tile) == Tile::Buildable
+        };
+        let max_towers = self.max_towers.read().unwrap().expect("Max Towers must be initialized");
+        buildable && self.tower_at(tile).is_none() && self.total_towers() < max_towers
+    }
+
+    /// Set the enemy the tower under the placement cursor targets
+    pub fn set_selected_tower_target(&self, target: TowerTarget) {
+        if let Some(tower_id) = self.selected_tower() {
+            *self.target_enemy.write().unwrap().get_mut(tower_id).expect("Entity Id must be valid") = Some(target);
         }
     }
 
@@ -311,87 +374,70 @@ impl TowerDefenseWorld {
         positions: Vec<u32>,
         healths: Vec<u32>,
     ) -> Vec<usize> {
-        let enemy_ids = self.add_entities(enemy_types.len());
+        let (speeds, layers, detected) = {
+            let catalog = self.enemy_catalog.read().unwrap();
+            let catalog = catalog.as_ref().expect("Enemy Catalog must be loaded");
+            let speeds = enemy_types.iter().map(|v| catalog.get(v).speed).collect();
+            let layers = enemy_types.iter().map(|v| catalog.get(v).layer).collect();
+            let detected = enemy_types.iter().map(|v| !catalog.get(v).stealth).collect();
+            (speeds, layers, detected)
+        };
+        let tiles = {
+            let map = self.map.read().unwrap();
+            let map = map.as_ref().expect("Map must be loaded");
+            positions.iter().map(|v| map.path_coordinates(*v)).collect()
+        };
+        let enemies = enemy_types.len();
+        let enemy_ids = self.add_entities(enemies);
+        self.set_move_progresss(&enemy_ids, vec![0; enemies]);
+        self.set_status_effectss(&enemy_ids, vec![Vec::new(); enemies]);
+        self.set_shields(&enemy_ids, vec![0; enemies]);
+        self.set_boss_phases(&enemy_ids, vec![0; enemies]);
+        self.set_temporary_shields(&enemy_ids, vec![TemporaryShield::default(); enemies]);
+        self.set_support_cooldowns(&enemy_ids, vec![0; enemies]);
+        self.set_detecteds(&enemy_ids, detected);
         self.set_enemy_types(&enemy_ids, enemy_types);
         self.set_sprites(&enemy_ids, sprites);
         self.set_enemy_positions(&enemy_ids, positions);
+        self.set_enemy_tiles(&enemy_ids, tiles);
+        self.set_enemy_layers(&enemy_ids, layers);
+        self.set_health_changes(&enemy_ids, vec![HealthChange::default(); enemies]);
         self.set_healths(&enemy_ids, healths);
+        self.set_speeds(&enemy_ids, speeds);
         enemy_ids
     }
 
-    /// Add a bunch of base enemies
-    pub fn add_base_enemies(&mut self, positions: Vec<u32>) -> Vec<usize> {
-        let healths = positions.iter().map(|_v| 1).collect();
-        self.add_enemies(
-            positions.iter().map(|_v| EnemyType::Base).collect(),
-            positions.iter().map(|_v| String::from("X")).collect(),
-            positions,
-            healths,
-        )
-    }
-
-    /// Add a bunch of second enemies
-    pub fn add_second_enemies(&mut self, positions: Vec<u32>) -> Vec<usize> {
-        let healths = positions.iter().map(|_v| 2).collect();
-        self.add_enemies(
-            positions.iter().map(|_v| EnemyType::Second).collect(),
-            positions.iter().map(|_v| String::from("Q")).collect(),
-            positions,
-            healths,
-        )
-    }
-
-    /// Add a bunch of third enemies
-    pub fn add_third_enemies(&mut self, positions: Vec<u32>) -> Vec<usize> {
-        let healths = positions.iter().map(|_v| 4).collect();
+    /// Add a bunch of enemies of one type with the sprite and health from the enemy catalog
+    pub fn add_catalog_enemies(&mut self, enemy_type: EnemyType, positions: Vec<u32>) -> Vec<usize> {
+        let (sprite, health) = {
+            let catalog = self.enemy_catalog.read().unwrap();
+            let definition = catalog.as_ref().expect("Enemy Catalog must be loaded").get(&enemy_type);
+            (definition.sprite.clone(), definition.health)
+        };
+        let enemies = positions.len();
         self.add_enemies(
-            positions.iter().map(|_v| EnemyType::Third).collect(),
-            positions.iter().map(|_v| String::from("T")).collect(),
+            vec![enemy_type; enemies],
+            vec![sprite; enemies],
             positions,
-            healths,
+            vec![health; enemies],
         )
     }
 
-    /// Add a bunch of fourth enemies
-    pub fn add_fourth_enemies(&mut self, positions: Vec<u32>) -> Vec<usize> {
-        let healths = positions.iter().map(|_v| 4).collect();
-        self.add_enemies(
-            positions.iter().map(|_v| EnemyType::Fourth).collect(),
-            positions.iter().map(|_v| String::from("U")).collect(),
-            positions,
-            healths,
-        )
-    }
-
-    /// Add a bunch of fifth enemies
-    pub fn add_fifth_enemies(&mut self, positions: Vec<u32>) -> Vec<usize> {
-        let healths = positions.iter().map(|_v| 6).collect();
-        self.add_enemies(
-            positions.iter().map(|_v| EnemyType::Fifth).collect(),
-            positions.iter().map(|_v| String::from("N")).collect(),
-            positions,
-            healths,
-        )
-    }
-
-    /// Add a bunch of final enemies
-    pub fn add_final_enemies(&mut self, positions: Vec<u32>) -> Vec<usize> {
-        let healths = positions.iter().map(|_v| 20).collect();
-        self.add_enemies(
-            positions.iter().map(|_v| EnemyType::Final).collect(),
-            positions.iter().map(|_v| String::from("M")).collect(),
-            positions,
-            healths,
-        )
-    }
-
-    /// Add a bunch of broken towers
-    pub fn add_broken_towers(&mut self, towers: usize) -> Vec<usize> {
+    /// Add a bunch of broken towers on the given tiles
+    pub fn add_broken_towers(&mut self, tower_tiles: Vec<(u32, u32)>) -> Vec<usize> {
+        let towers = tower_tiles.len();
+        let sprite = self.tower_catalog.read().unwrap().as_ref()
+            .expect("Tower Catalog must be loaded")
+            .get(&TowerType::broken())
+            .sprite
+            .clone();
         let tower_ids = self.add_entities(towers);
-        self.set_tower_types(&tower_ids, vec![TowerType::Broken; towers]);
+        self.set_tower_types(&tower_ids, vec![TowerType::broken(); towers]);
         self.set_target_enemys(&tower_ids, vec![TowerTarget::First; towers]);
-        self.set_tower_boundss(&tower_ids, vec![(0, 0); towers]);
-        self.set_sprites(&tower_ids, vec![String::from("-"); towers]);
+        self.set_tower_tiles(&tower_ids, tower_tiles);
+        self.set_attack_cooldowns(&tower_ids, vec![0; towers]);
+        self.set_invested_pointss(&tower_ids, vec![0; towers]);
+        self.set_sprites(&tower_ids, vec![sprite; towers]);
         tower_ids
     }
 
@@ -401,15 +447,150 @@ impl TowerDefenseWorld {
         base_health: u32,
     ) {
         self.set_base_health(base_health);
+        self.set_wave_start_base_health(base_health);
         self.set_base_damage(0);
         self.set_removal_entities(Vec::new());
+        self.set_fired_projectiles(Vec::new());
         self.set_alive_enemies(0);
-        self.set_selected_tower(0);
+        self.set_cursor((0, 0));
         self.set_help_displayed(false);
         self.set_points(10);
         self.set_level(1);
         self.set_upgrading_tower(false);
+        self.set_branching_tower(None);
         self.set_downgrading_tower(false);
+        self.set_demolishing_tower(false);
+        self.set_game_phase(GamePhase::Playing);
+        self.set_enemies_killed(0);
+        self.set_overkill_damage(0);
+        self.set_restarting_game(false);
+
+        // Keep settings, catalogs, and maps loaded from a file between games
+        if self.max_towers.read().unwrap().is_none() {
+            self.set_max_towers(DEFAULT_MAX_TOWERS);
+        }
+        if self.refund_percent.read().unwrap().is_none() {
+            self.set_refund_percent(DEFAULT_REFUND_PERCENT);
+        }
+        if self.rng.read().unwrap().is_none() {
+            self.set_rng(Rng::new(DEFAULT_SEED));
+        }
+        if self.tower_catalog.read().unwrap().is_none() {
+            self.set_tower_catalog(TowerCatalog::default());
+        }
+        if self.enemy_catalog.read().unwrap().is_none() {
+            self.set_enemy_catalog(EnemyCatalog::default());
+        }
+        if self.wave_catalog.read().unwrap().is_none() {
+            self.set_wave_catalog(WaveCatalog::default());
+        }
+        if self.map.read().unwrap().is_none() {
+            self.set_map(Map::default());
+        }
+    }
+
+    /// Set up the towers and enemies for the start of a game
+    pub fn new_game(
+        &mut self,
+        base_health: u32,
+    ) {
+        // Initialize Singular Components
+        self.initialize_singular_components(base_health);
+
+        // Start with a real tower on the first buildable tile
+        let first_tile = self.map.read().unwrap().as_ref()
+            .expect("Map must be loaded")
+            .buildable_tiles()
+            .first()
+            .copied();
+        if let Some(first_tile) = first_tile {
+            let tower_id = self.add_broken_tower(first_tile);
+            self.upgrade_tower(tower_id, &TowerType::broken());
+            self.set_cursor(first_tile);
+        }
+
+        // Add the first wave of enemies
+        self.spawn_wave(1);
+    }
+
+    /// Add the enemies in the wave for a given level behind the enemy spawn
+    pub fn spawn_wave(&mut self, level: u32) {
+        let wave = self.wave_catalog.read().unwrap().as_ref()
+            .expect("Wave Catalog must be loaded")
+            .wave(level);
+        let path_length = self.map.read().unwrap().as_ref()
+            .expect("Map must be loaded")
+            .path_length();
+        for group in wave.groups {
+            let positions = group.positions(path_length);
+            self.add_catalog_enemies(group.enemy, positions);
+        }
+        let base_health = self.base_health.read().unwrap().expect("Base Health must be initialized");
+        self.set_wave_start_base_health(base_health);
+    }
+
+    /// The points that would be paid out if the current wave ended now
+    pub fn wave_income(&self) -> WaveIncome {
+        let wave_catalog = self.wave_catalog.read().unwrap();
+        let rewards = &wave_catalog.as_ref().expect("Wave Catalog must be loaded").rewards;
+        let tower_catalog = self.tower_catalog.read().unwrap();
+        let tower_catalog = tower_catalog.as_ref().expect("Tower Catalog must be loaded");
+
+        let points = self.points.read().unwrap().expect("Points must be initialized");
+        let base_health = self.base_health.read().unwrap().expect("Base Health must be initialized");
+        let wave_start_base_health = self.wave_start_base_health.read().unwrap()
+            .expect("Wave Start Base Health must be initialized");
+        WaveIncome {
+            interest: rewards.interest(points),
+            clear_bonus: if base_health >= wave_start_base_health { rewards.clear_bonus } else { 0 },
+            tower_income: self.tower_type.read().unwrap().iter()
+                .flatten()
+                .map(|tower_type| tower_catalog.get(tower_type).income)
+                .sum(),
+        }
+    }
+
+    /// Add shots fired by towers
+    pub fn add_projectiles(&mut self, projectiles: Vec<Projectile>) -> Vec<usize> {
+        let projectile_ids = self.add_entities(projectiles.len());
+        self.set_projectiles(&projectile_ids, projectiles);
+        projectile_ids
+    }
+
+    /// Remove some entities, keeping every projectile (including the shots fired this tick that
+    /// haven't been added yet) pointed at its target as the entity ids after a removed entity
+    /// shift down (projectiles at removed entities miss)
+    pub fn despawn_entities(&mut self, mut entity_ids: Vec<usize>) {
+        entity_ids.sort_unstable_by(|a, b| b.cmp(a));
+        entity_ids.dedup();
+        let retarget = |projectile: &mut Projectile| {
+            projectile.target = projectile.target.and_then(|target| match entity_ids.binary_search_by(|v| target.cmp(v)) {
+                Ok(_) => None,
+                Err(removed_before) => Some(target - (entity_ids.len() - removed_before)),
+            });
+        };
+        self.projectile.write().unwrap().iter_mut().flatten().for_each(retarget);
+        self.fired_projectiles.write().unwrap().iter_mut().flatten().for_each(retarget);
+        self.remove_entities(entity_ids);
+    }
+
+    /// Remove every entity from the world
+    pub fn remove_all_entities(&mut self) {
+        let total_entities = self.total_entities();
+        self.remove_entities((0..total_entities).rev().collect());
+    }
+
+    /// Remove every entity and start a new game (with the random numbers starting over from the seed)
+    pub fn reset(
+        &mut self,
+        base_health: u32,
+    ) {
+        self.remove_all_entities();
+        let rng = self.rng.read().unwrap().map(|v| v.restarted());
+        if let Some(rng) = rng {
+            self.set_rng(rng);
+        }
+        self.new_game(base_health);
     }
 
     pub fn print_world(&mut self) {
@@ -420,12 +601,28 @@ impl TowerDefenseWorld {
         println!("Healths: {:?}", self.health.read().unwrap());
         println!("Health Changes: {:?}", self.health_change.read().unwrap());
         println!("Enemy Positions: {:?}", self.enemy_position.read().unwrap());
+        println!("Enemy Tiles: {:?}", self.enemy_tile.read().unwrap());
+        println!("Enemy Layers: {:?}", self.enemy_layer.read().unwrap());
+        println!("Speeds: {:?}", self.speed.read().unwrap());
+        println!("Move Progress: {:?}", self.move_progress.read().unwrap());
+        println!("Status Effects: {:?}", self.status_effects.read().unwrap());
+        println!("Shields: {:?}", self.shield.read().unwrap());
+        println!("Boss Phases: {:?}", self.boss_phase.read().unwrap());
+        println!("Temporary Shields: {:?}", self.temporary_shield.read().unwrap());
+        println!("Support Cooldowns: {:?}", self.support_cooldown.read().unwrap());
+        println!("Detected: {:?}", self.detected.read().unwrap());
         println!("Target Enemies: {:?}", self.target_enemy.read().unwrap());
-        println!("Tower Bounds: {:?}", self.tower_bounds.read().unwrap());
+        println!("Tower Tiles: {:?}", self.tower_tile.read().unwrap());
+        println!("Attack Cooldowns: {:?}", self.attack_cooldown.read().unwrap());
+        println!("Invested Points: {:?}", self.invested_points.read().unwrap());
+        println!("Projectiles: {:?}", self.projectile.read().unwrap());
         println!("Base Health: {:?}", self.base_health.read().unwrap());
         println!("Base Damage: {:?}", self.base_damage.read().unwrap());
         println!("Removal Entities: {:?}", self.removal_entities.read().unwrap());
         println!("Alive Enemies: {:?}", self.alive_enemies.read().unwrap());
+        println!("Cursor: {:?}", self.cursor.read().unwrap());
+        println!("Game Phase: {:?}", self.game_phase.read().unwrap());
+        println!("Enemies Killed: {:?}", self.enemies_killed.read().unwrap());
         println!("\n");
     }
 }